@@ -1,3 +1,4 @@
+use heck::ShoutySnakeCase;
 use proc_macro2::Span;
 use quote::quote;
 use quote::ToTokens;
@@ -8,10 +9,14 @@ pub fn quote(item: syn::Item) -> proc_macro2::TokenStream {
             let contract = Contract::from_item_trait(item_trait);
             let disp = generate_dispatcher(&contract);
             let trait_and_event = generate_event(&contract);
+            let abi = generate_abi(&contract);
+            let client = generate_client(&contract);
             quote! {
                 extern crate alloc;
                 #trait_and_event
                 #disp
+                #abi
+                #client
             }
         }
         _ => {
@@ -127,57 +132,98 @@ impl ContractEvent {
     }
 }
 
+fn is_result_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(path) => {
+            path.path.segments.last().map(|seg| seg.ident == "Result").unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+/// extract `T` from `Result<T, E>`
+fn result_ok_type(ty: &syn::Type) -> Option<&syn::Type> {
+    if !is_result_type(ty) {
+        return None;
+    }
+    match ty {
+        syn::Type::Path(path) => match path.path.segments.last()?.arguments {
+            syn::PathArguments::AngleBracketed(ref args) => match args.args.first()? {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 fn generate_dispatcher(contract: &Contract) -> proc_macro2::TokenStream {
     let actions: Vec<proc_macro2::TokenStream> = contract.fields.iter().filter_map(|field| {
         match field {
             ContractField::Action(ref action) => {
                 let action_name = &action.name;
                 let action_literal = syn::LitStr::new(&action_name.to_string(), proc_macro2::Span::call_site());
-                let args = action.params.iter().map(|&(_, ref ty)| {
+                let mut reads = Vec::new();
+                let mut args = Vec::new();
+                for (i, &(ref pat, ref ty)) in action.params.iter().enumerate() {
+                    let arg_literal = syn::LitStr::new(&pat_name(pat), proc_macro2::Span::call_site());
+                    let arg_err = quote! { .map_err(|err| ontio_std::abi::DispatchError::decode_failed(#arg_literal, &err))? };
+                    let arg = syn::Ident::new(&format!("arg{}", i), proc_macro2::Span::call_site());
                     let ty :&syn::Type = ty;
-                    match ty {
+                    let (read, pass) = match ty {
                         syn::Type::Reference(refer) => {
                             let mutability = refer.mutability.as_ref();
                             let real = *refer.elem.clone();
                             match real {
+                                ref real if mutability.is_none() && is_borrowed_type(real) => {
+                                    (quote! { let #arg = source.read::<&#real>()#arg_err; }, quote! { #arg })
+                                }
                                 syn::Type::Slice(slice) => {
                                     let slice_elem = &slice.elem;
                                     match mutability {
-                                        Some(_) => quote! { source.read::<alloc::vec::Vec<#slice_elem>>().expect(arg_decode_err).as_mut_slice() },
-                                        None => quote! { source.read::<alloc::vec::Vec<#slice_elem>>().expect(arg_decode_err).as_slice() },
-                                    }
-                                }
-                                syn::Type::Path(ref path) => {
-                                    if path.clone().into_token_stream().to_string() == "str" {
-                                        quote! { source.read::<String>().expect(arg_decode_err).as_str() }
-                                    } else {
-                                        quote! { &#mutability source.read::<#real>().expect(arg_decode_err) }
+                                        Some(_) => (quote! { let mut #arg = source.read::<alloc::vec::Vec<#slice_elem>>()#arg_err; }, quote! { #arg.as_mut_slice() }),
+                                        None => (quote! { let #arg = source.read::<alloc::vec::Vec<#slice_elem>>()#arg_err; }, quote! { #arg.as_slice() }),
                                     }
                                 }
-                                real => quote! { &#mutability source.read::<#real>().expect(arg_decode_err) },
+                                real => (quote! { let #mutability #arg = source.read::<#real>()#arg_err; }, quote! { &#mutability #arg }),
                             }
                         }
-                        ty => {
-                            quote! { source.read::<#ty>().expect(arg_decode_err) }
-                        }
-                    }
-                });
+                        ty => (quote! { let #arg = source.read::<#ty>()#arg_err; }, quote! { #arg }),
+                    };
+                    reads.push(read);
+                    args.push(pass);
+                }
+                let reads = quote! {
+                    #(#reads)*
+                    source.finish().map_err(|err| ontio_std::abi::DispatchError::decode_failed("payload", &err))?;
+                };
                 match action.ret {
+                    Some(ref ret) if is_result_type(ret) => {
+                        Some(quote!{
+                            #action_literal => {
+                                #reads
+                                let res = contract_instance.#action_name(#(#args),*)
+                                    .map_err(|err| ontio_std::abi::DispatchError::from_error(&err));
+                                Ok(ontio_std::abi::Sink::with_value(&res).into())
+                            }
+                        })
+                    }
                     Some(_) => {
                         Some(quote!{
                             #action_literal => {
+                                #reads
                                 let res = contract_instance.#action_name(#(#args),*);
-                                let mut sink = ontio_std::abi::Sink::new(16);
-                                sink.write(res);
-                                sink.into()
+                                Ok(ontio_std::abi::Sink::with_value(&res).into())
                             }
                         })
                     }
                     None => {
                         Some(quote!{
                             #action_literal => {
-                                contract_instance.#action_name(#(source.read::<#args>().expect(arg_decode_err)),*);
-                                alloc::vec::Vec::new()
+                                #reads
+                                contract_instance.#action_name(#(#args),*);
+                                Ok(alloc::vec::Vec::new())
                             }
                         })
                     }
@@ -207,18 +253,202 @@ fn generate_dispatcher(contract: &Contract) -> proc_macro2::TokenStream {
         }
 
         impl<T: #contract_name> ontio_std::abi::Dispatcher for #dispatcher_name<T> {
-            fn dispatch(&mut self, payload: &[u8]) -> alloc::vec::Vec<u8> {
+            fn try_dispatch(&mut self, payload: &[u8]) -> core::result::Result<alloc::vec::Vec<u8>, ontio_std::abi::DispatchError> {
                 let contract_instance = &mut self.contract_instance;
-                // todo: avoid bytes copy
-                let mut source = ontio_std::abi::Source::new(payload);
-                let action = source.read::<String>().unwrap();
-                let arg_decode_err = "failed to decode argument";
+                let mut source = ontio_std::abi::Source::strict(payload);
+                let action = source.read::<String>()
+                    .map_err(|err| ontio_std::abi::DispatchError::decode_failed("method", &err))?;
                 match action.as_str() {
                     #(#actions,)*
-                    _ => panic!("invoke unsupported method"),
+                    _ => Err(ontio_std::abi::DispatchError::unsupported_method(&action)),
                 }
             }
+
+            fn dispatch(&mut self, payload: &[u8]) -> alloc::vec::Vec<u8> {
+                self.try_dispatch(payload).unwrap_or_else(|err| ontio_std::runtime::panic(&err.message))
+            }
+        }
+    }
+}
+
+fn generate_client(contract: &Contract) -> proc_macro2::TokenStream {
+    let methods: Vec<proc_macro2::TokenStream> = contract
+        .fields
+        .iter()
+        .filter_map(|field| match field {
+            ContractField::Action(ref action) => {
+                let action_name = &action.name;
+                let action_literal = syn::LitStr::new(&action_name.to_string(), Span::call_site());
+                let params = action.params.iter().map(|&(ref pat, ref ty)| quote! { #pat: #ty });
+                let args = action.params.iter().map(|&(ref pat, ref ty)| match **ty {
+                    syn::Type::Reference(syn::TypeReference { mutability: Some(_), .. }) => {
+                        quote! { &*#pat }
+                    }
+                    _ => quote! { #pat },
+                });
+                let args: Vec<_> = args.collect();
+                let encode = quote! {
+                    let len = [
+                        ontio_std::abi::Encoder::encoded_len(&#action_literal),
+                        #(ontio_std::abi::Encoder::encoded_len(&#args)),*
+                    ];
+                    let mut sink = ontio_std::abi::Sink::new(
+                        len.iter().cloned().sum::<Option<usize>>().unwrap_or(16)
+                    );
+                    sink.write(#action_literal);
+                    #(sink.write(#args);)*
+                };
+                let method = match action.ret {
+                    Some(ref ret) => {
+                        let ret = match result_ok_type(ret) {
+                            Some(ok) => quote! {
+                                core::result::Result<#ok, ontio_std::abi::DispatchError>
+                            },
+                            None => quote! { #ret },
+                        };
+                        quote! {
+                            pub fn #action_name(&self, #(#params),*) -> #ret {
+                                #encode
+                                let output = ontio_std::runtime::call_contract(&self.address, sink.bytes());
+                                let mut source = ontio_std::abi::Source::strict(&output);
+                                let res = source.read::<#ret>().and_then(|res| source.finish().map(|_| res));
+                                res.expect("failed to decode return value")
+                            }
+                        }
+                    }
+                    None => quote! {
+                        pub fn #action_name(&self, #(#params),*) {
+                            #encode
+                            let output = ontio_std::runtime::call_contract(&self.address, sink.bytes());
+                            ontio_std::abi::Source::strict(&output).finish().expect("failed to decode return value");
+                        }
+                    },
+                };
+                Some(method)
+            }
+            _ => None,
+        })
+        .collect();
+
+    let client_name = syn::Ident::new(&format!("{}Client", contract.name), Span::call_site());
+
+    quote! {
+        /// Client used to call the contract from another contract.
+        pub struct #client_name {
+            pub address: ontio_std::types::Address,
         }
+
+        impl #client_name {
+            pub fn new(address: ontio_std::types::Address) -> Self {
+                Self { address }
+            }
+
+            #(#methods)*
+        }
+    }
+}
+
+fn pat_name(pat: &syn::Pat) -> String {
+    match pat {
+        syn::Pat::Ident(ident) => ident.ident.to_string(),
+        pat => pat.to_token_stream().to_string(),
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// whether a shared reference argument is decoded by borrowing from the input instead of copying,
+/// eg: `&str`, `&[u8]`, `&ontio_std::types::Address`. only fully qualified paths are matched, as the
+/// macro can not resolve imports: `&Address` is decoded as an owned value then passed by reference
+fn is_borrowed_type(ty: &syn::Type) -> bool {
+    const BORROWED: [&str; 3] = ["str", "ontio_std::types::Address", "ontio_std::types::H256"];
+    match ty {
+        syn::Type::Slice(slice) => type_name(&slice.elem) == "u8",
+        syn::Type::Path(path) if path.qself.is_none() => BORROWED.contains(&&*type_name(ty)),
+        _ => false,
+    }
+}
+
+/// render type as compact string, eg: `&[(Address, U128)]` => `&[(Address,U128)]`
+fn type_name(ty: &syn::Type) -> String {
+    let mut name = String::new();
+    for part in ty.to_token_stream().to_string().split(' ') {
+        if let (Some(last), Some(first)) = (name.chars().last(), part.chars().next()) {
+            if is_ident_char(last) && is_ident_char(first) {
+                name.push(' ');
+            }
+        }
+        name.push_str(part);
+    }
+    name
+}
+
+fn json_string(val: &str) -> String {
+    format!("\"{}\"", val.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn json_params(params: &[(Box<syn::Pat>, Box<syn::Type>)]) -> String {
+    let params: Vec<String> = params
+        .iter()
+        .map(|&(ref pat, ref ty)| {
+            format!(
+                "{{\"name\":{},\"type\":{}}}",
+                json_string(&pat_name(pat)),
+                json_string(&type_name(ty))
+            )
+        })
+        .collect();
+    format!("[{}]", params.join(","))
+}
+
+fn generate_abi(contract: &Contract) -> proc_macro2::TokenStream {
+    let mut methods = Vec::new();
+    let mut events = Vec::new();
+    for field in contract.fields.iter() {
+        match field {
+            ContractField::Action(ref action) => {
+                //the dispatcher encodes the error of a `Result` as a `DispatchError`
+                let ret = match action.ret {
+                    Some(ref ret) => match result_ok_type(ret) {
+                        Some(ok) => format!("Result<{},DispatchError>", type_name(ok)),
+                        None => type_name(ret),
+                    },
+                    None => "()".to_string(),
+                };
+                methods.push(format!(
+                    "{{\"name\":{},\"parameters\":{},\"return_type\":{}}}",
+                    json_string(&action.name.to_string()),
+                    json_params(&action.params),
+                    json_string(&ret)
+                ));
+            }
+            ContractField::Event(ref event) => {
+                events.push(format!(
+                    "{{\"name\":{},\"parameters\":{}}}",
+                    json_string(&event.name.to_string()),
+                    json_params(&event.params)
+                ));
+            }
+            ContractField::Unhandle(_) => (),
+        }
+    }
+    let abi = format!(
+        "{{\"name\":{},\"methods\":[{}],\"events\":[{}]}}",
+        json_string(&contract.name.to_string()),
+        methods.join(","),
+        events.join(",")
+    );
+    let abi = syn::LitStr::new(&abi, Span::call_site());
+    let abi_name = syn::Ident::new(
+        &format!("{}_ABI", contract.name.to_string().to_shouty_snake_case()),
+        Span::call_site(),
+    );
+
+    quote! {
+        /// JSON description of the contract methods and events.
+        pub const #abi_name: &str = #abi;
     }
 }
 
@@ -231,9 +461,11 @@ fn generate_event(contract: &Contract) -> proc_macro2::TokenStream {
                 let event_sig = &event.method_sig;
                 let event_body = {
                     let args_type = event.params.iter().map(|&(_, ref ty)| quote! { #ty });
-                    let args_name = event.params.iter().map(|&(ref pat, _)| quote! { #pat });
+                    let args_name: Vec<_> = event.params.iter().map(|&(ref pat, _)| quote! { #pat }).collect();
+                    let args_count = args_name.len();
                     quote! { {
-                        let mut sink = ontio_std::abi::Sink::new(16);
+                        let len: [Option<usize>; #args_count] = [#(ontio_std::abi::Encoder::encoded_len(&#args_name)),*];
+                        let mut sink = ontio_std::abi::Sink::new(len.iter().cloned().sum::<Option<usize>>().unwrap_or(16));
                         #(sink.write::<#args_type>(#args_name);)*
                         ontio_std::runtime::notify(&sink.into());
                     } }
@@ -12,6 +12,7 @@ use syn::{parse_macro_input, Lit};
 mod base58;
 mod contract;
 mod event;
+mod neo_contract;
 
 #[proc_macro_attribute]
 pub fn contract(_metadata: TokenStream, input: TokenStream) -> TokenStream {
@@ -21,6 +22,14 @@ pub fn contract(_metadata: TokenStream, input: TokenStream) -> TokenStream {
     stream.into()
 }
 
+#[proc_macro_attribute]
+pub fn neo_contract(_metadata: TokenStream, input: TokenStream) -> TokenStream {
+    let item: syn::Item = syn::parse(input).unwrap();
+    let stream = neo_contract::quote(item);
+
+    stream.into()
+}
+
 #[proc_macro_attribute]
 pub fn event(metadata: TokenStream, input: TokenStream) -> TokenStream {
     match syn::parse::<syn::Item>(input).unwrap() {
@@ -0,0 +1,109 @@
+use heck::MixedCase;
+use proc_macro2::Span;
+use quote::quote;
+
+pub fn quote(item: syn::Item) -> proc_macro2::TokenStream {
+    match item {
+        syn::Item::Trait(item_trait) => generate_client(&item_trait),
+        _ => {
+            panic!("`#[neo_contract]` can only be used on a trait");
+        }
+    }
+}
+
+struct NeoMethod {
+    name: syn::Ident,
+    method_name: String,
+    params: Vec<(Box<syn::Pat>, Box<syn::Type>)>,
+    ret: Option<syn::Type>,
+}
+
+impl NeoMethod {
+    fn from_trait_method(method: &syn::TraitItemMethod) -> Self {
+        //the method is called by the mixed case of its name unless renamed with `#[name = "..."]`
+        let method_name = method
+            .attrs
+            .iter()
+            .find(|attr| attr.path.is_ident("name"))
+            .map(|attr| match attr.parse_meta() {
+                Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                    lit: syn::Lit::Str(name), ..
+                })) => name.value(),
+                _ => panic!("expected `#[name = \"...\"]`"),
+            })
+            .unwrap_or_else(|| method.sig.ident.to_string().to_mixed_case());
+        let params = method
+            .sig
+            .inputs
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::FnArg::Receiver(_) => None,
+                syn::FnArg::Typed(ref capt) => Some((capt.pat.clone(), capt.ty.clone())),
+            })
+            .collect();
+        let ret = match method.sig.output {
+            syn::ReturnType::Default => None,
+            syn::ReturnType::Type(_, ref ty) => Some(*ty.clone()),
+        };
+
+        NeoMethod { name: method.sig.ident.clone(), method_name, params, ret }
+    }
+}
+
+fn generate_client(item_trait: &syn::ItemTrait) -> proc_macro2::TokenStream {
+    let methods: Vec<proc_macro2::TokenStream> = item_trait
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            syn::TraitItem::Method(ref method) => Some(NeoMethod::from_trait_method(method)),
+            _ => None,
+        })
+        .map(|method| {
+            let name = &method.name;
+            let method_literal = syn::LitStr::new(&method.method_name, Span::call_site());
+            let params = method.params.iter().map(|&(ref pat, ref ty)| quote! { #pat: #ty });
+            let args = method.params.iter().map(|&(ref pat, ref ty)| match **ty {
+                syn::Type::Reference(syn::TypeReference { mutability: Some(_), .. }) => {
+                    quote! { &*#pat }
+                }
+                _ => quote! { #pat },
+            });
+            //the method name is followed by the list of arguments
+            let call = quote! {
+                ontio_std::contract::neo::call_method(&self.address, #method_literal, (#(#args,)*))
+            };
+            match method.ret {
+                Some(ref ret) => quote! {
+                    pub fn #name(&self, #(#params),*) -> #ret {
+                        let output = #call;
+                        let mut parser = ontio_std::abi::VmValueParser::new(&output);
+                        parser.read::<#ret>().expect("failed to decode return value")
+                    }
+                },
+                None => quote! {
+                    pub fn #name(&self, #(#params),*) {
+                        #call;
+                    }
+                },
+            }
+        })
+        .collect();
+
+    let vis = &item_trait.vis;
+    let client_name = syn::Ident::new(&format!("{}Client", item_trait.ident), Span::call_site());
+
+    quote! {
+        /// Client used to call the NeoVM contract.
+        #vis struct #client_name {
+            pub address: ontio_std::types::Address,
+        }
+
+        impl #client_name {
+            pub fn new(address: ontio_std::types::Address) -> Self {
+                Self { address }
+            }
+
+            #(#methods)*
+        }
+    }
+}
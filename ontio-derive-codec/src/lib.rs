@@ -32,6 +32,15 @@ pub fn derive_encoder(item: TokenStream) -> TokenStream {
                              }), *
                          }
                     }
+
+                    fn encoded_len(&self) -> Option<usize> {
+                         match self {
+                             #(#name::#get_selfs(temp) => {
+                                Some(ontio_std::abi::Encoder::encoded_len(&stringify!(#get_selfs))?
+                                    + ontio_std::abi::Encoder::encoded_len(temp)?)
+                             }), *
+                         }
+                    }
                   }
             };
             implemented_encoder
@@ -53,6 +62,10 @@ pub fn derive_encoder(item: TokenStream) -> TokenStream {
                         fn encode(&self, sink: &mut ontio_std::abi::Sink) {
                              sink.write((#(#get_selfs),*));
                         }
+
+                        fn encoded_len(&self) -> Option<usize> {
+                             ontio_std::abi::Encoder::encoded_len(&(#(#get_selfs),*))
+                        }
                     }
                 };
                 implemented_encoder
@@ -100,7 +113,7 @@ pub fn derive_decoder(item: TokenStream) -> TokenStream {
                     let ty:&str= source.read()?;
                         match ty {
                              #(stringify!(#get_selfs) => {
-                                let temp = source.read()?;
+                                let temp = source.read().map_err(|err: ontio_std::abi::Error| err.with_field(stringify!(#get_selfs2)))?;
                                 Ok(#name::#get_selfs2(temp))
                              }),*
                              _ => {
@@ -129,7 +142,7 @@ pub fn derive_decoder(item: TokenStream) -> TokenStream {
                         fn decode(source: &mut ontio_std::abi::Source) -> Result<Self,
                         ontio_std::abi::Error> {
                             return Ok(#name {
-                            #(#get_selfs: source.read()?),*
+                            #(#get_selfs: source.read().map_err(|err: ontio_std::abi::Error| err.with_field(stringify!(#get_selfs)))?),*
                             })
                         }
                     }
@@ -144,3 +157,165 @@ pub fn derive_decoder(item: TokenStream) -> TokenStream {
 
     expanded.into()
 }
+
+#[proc_macro_derive(VmValueEncoder)]
+pub fn derive_vm_value_encoder(item: TokenStream) -> TokenStream {
+    let ast: syn::DeriveInput = syn::parse(item).unwrap();
+    let name = &ast.ident; //struct name
+    let body = match ast.data {
+        Data::Enum(DataEnum { ref variants, .. }) => {
+            //an enum is written as a list of the variant name followed by its fields
+            let arms: Vec<_> = variants
+                .iter()
+                .map(|variant| {
+                    let variant_name = &variant.ident;
+                    let bindings = field_bindings(&variant.fields);
+                    let pattern = field_pattern(&variant.fields, &bindings);
+                    quote! {
+                        #name::#variant_name #pattern => {
+                            builder.write((stringify!(#variant_name), #(#bindings,)*));
+                        }
+                    }
+                })
+                .collect();
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Struct(DataStruct { ref fields, .. }) => {
+            //a struct is written as a list of its fields
+            let bindings = field_bindings(fields);
+            let pattern = field_pattern(fields, &bindings);
+            quote! {
+                let #name #pattern = self;
+                builder.write((#(#bindings,)*));
+            }
+        }
+        _ => panic!("not support"),
+    };
+    let expanded = quote! {
+        impl ontio_std::abi::VmValueEncoder for #name {
+            fn serialize(&self, builder: &mut ontio_std::abi::VmValueBuilder) {
+                #body
+            }
+        }
+    };
+    expanded.into()
+}
+
+#[proc_macro_derive(VmValueDecoder)]
+pub fn derive_vm_value_decoder(item: TokenStream) -> TokenStream {
+    let ast: syn::DeriveInput = syn::parse(item).unwrap();
+    let name = &ast.ident; //struct name
+    let body = match ast.data {
+        Data::Enum(DataEnum { ref variants, .. }) => {
+            let arms: Vec<_> = variants
+                .iter()
+                .map(|variant| {
+                    let variant_name = &variant.ident;
+                    let count = variant.fields.len() as u32 + 1;
+                    let value = read_fields(
+                        quote! { #name::#variant_name },
+                        &variant.fields,
+                        Some(variant_name),
+                    );
+                    quote! {
+                        stringify!(#variant_name) => {
+                            if len != #count {
+                                return Err(parser.source.error(ontio_std::abi::ErrorKind::LengthInconsistency));
+                            }
+                            Ok(#value)
+                        }
+                    }
+                })
+                .collect();
+            quote! {
+                let len = parser.list()?;
+                if len == 0 {
+                    return Err(parser.source.error(ontio_std::abi::ErrorKind::LengthInconsistency));
+                }
+                let ty = parser.string()?;
+                match ty {
+                    #(#arms)*
+                    _ => Err(parser.source.error(ontio_std::abi::ErrorKind::IrregularData)),
+                }
+            }
+        }
+        Data::Struct(DataStruct { ref fields, .. }) => {
+            let count = fields.len() as u32;
+            let value = read_fields(quote! { #name }, fields, None);
+            quote! {
+                if parser.list()? != #count {
+                    return Err(parser.source.error(ontio_std::abi::ErrorKind::LengthInconsistency));
+                }
+                Ok(#value)
+            }
+        }
+        _ => panic!("not support"),
+    };
+    let expanded = quote! {
+        impl<'a> ontio_std::abi::VmValueDecoder<'a> for #name {
+            fn deserialize(parser: &mut ontio_std::abi::VmValueParser<'a>) -> Result<Self, ontio_std::abi::Error> {
+                #body
+            }
+        }
+    };
+    expanded.into()
+}
+
+//local names the fields are bound to when destructuring a struct or variant
+fn field_bindings(fields: &Fields) -> Vec<syn::Ident> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match field.ident {
+            Some(ref ident) => ident.clone(),
+            None => quote::format_ident!("field{}", i),
+        })
+        .collect()
+}
+
+fn field_pattern(fields: &Fields, bindings: &[syn::Ident]) -> proc_macro2::TokenStream {
+    match fields {
+        Fields::Named(_) => quote! { { #(#bindings),* } },
+        Fields::Unnamed(_) => quote! { ( #(#bindings),* ) },
+        Fields::Unit => quote! {},
+    }
+}
+
+//construct `path` reading its fields in order, errors are tagged with the field and variant names
+fn read_fields(
+    path: proc_macro2::TokenStream, fields: &Fields, variant: Option<&syn::Ident>,
+) -> proc_macro2::TokenStream {
+    let read = |field_name: Option<&syn::Ident>| {
+        let mut map_err = quote! {};
+        if let Some(field_name) = field_name {
+            map_err = quote! { #map_err.with_field(stringify!(#field_name)) };
+        }
+        if let Some(variant) = variant {
+            map_err = quote! { #map_err.with_field(stringify!(#variant)) };
+        }
+        quote! { parser.read().map_err(|err: ontio_std::abi::Error| err #map_err)? }
+    };
+    match fields {
+        Fields::Named(ref fields_name) => {
+            let values: Vec<_> = fields_name
+                .named
+                .iter()
+                .map(|field| {
+                    let field_name = field.ident.as_ref().unwrap();
+                    let value = read(Some(field_name));
+                    quote! { #field_name: #value }
+                })
+                .collect();
+            quote! { #path { #(#values),* } }
+        }
+        Fields::Unnamed(ref fields_unnamed) => {
+            let values: Vec<_> = fields_unnamed.unnamed.iter().map(|_| read(None)).collect();
+            quote! { #path ( #(#values),* ) }
+        }
+        Fields::Unit => path,
+    }
+}
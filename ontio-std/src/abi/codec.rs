@@ -1,15 +1,21 @@
-use super::Error;
 use super::Sink;
 use super::{Decoder, Encoder, VmValueBuilder, VmValueDecoder, VmValueEncoder, VmValueParser};
+use super::{Error, ErrorKind};
 
+use super::source::varuint_encode_size;
 use crate::abi::Source;
 use crate::prelude::*;
-use crate::types::{Address, H256};
+use crate::types::{Address, H256, U256};
+use alloc::collections::{BTreeMap, BTreeSet};
 
 impl<'a> Decoder<'a> for u8 {
     fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
         source.read_byte()
     }
+
+    fn decode_vec(source: &mut Source<'a>, len: usize) -> Result<Vec<Self>, Error> {
+        Ok(source.next_bytes(len)?.to_vec())
+    }
 }
 
 impl<'a> Decoder<'a> for &'a Address {
@@ -34,13 +40,49 @@ impl<'a> Decoder<'a> for &'a [u8] {
 
 impl<'a, T: Decoder<'a>> Decoder<'a> for Vec<T> {
     fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
-        let len = source.read_varuint()?;
-        let mut value = Vec::with_capacity(cmp::min(len, 1024) as usize);
+        let len = source.read_length()?;
+        T::decode_vec(source, len)
+    }
+}
+
+impl<'a, T: Decoder<'a>> Decoder<'a> for Box<T> {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        Ok(Box::new(source.read()?))
+    }
+}
+
+//entries must be in strictly increasing order, so that a map has a single encoding
+impl<'a, K: Decoder<'a> + Ord, V: Decoder<'a>> Decoder<'a> for BTreeMap<K, V> {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        let len = source.read_length()?;
+        let mut map = BTreeMap::new();
         for _i in 0..len {
-            value.push(source.read::<T>()?);
+            let key: K = source.read()?;
+            if let Some((last, _)) = map.iter().next_back() {
+                if *last >= key {
+                    return Err(source.error(ErrorKind::IrregularData));
+                }
+            }
+            map.insert(key, source.read()?);
         }
+        Ok(map)
+    }
+}
 
-        Ok(value)
+impl<'a, T: Decoder<'a> + Ord> Decoder<'a> for BTreeSet<T> {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        let len = source.read_length()?;
+        let mut set = BTreeSet::new();
+        for _i in 0..len {
+            let item: T = source.read()?;
+            if let Some(last) = set.iter().next_back() {
+                if *last >= item {
+                    return Err(source.error(ErrorKind::IrregularData));
+                }
+            }
+            set.insert(item);
+        }
+        Ok(set)
     }
 }
 
@@ -55,10 +97,21 @@ impl<'a, T: Decoder<'a>> Decoder<'a> for Option<T> {
     }
 }
 
+impl<'a, T: Decoder<'a>, E: Decoder<'a>> Decoder<'a> for Result<T, E> {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        let is_ok: bool = source.read()?;
+        if is_ok {
+            Ok(Ok(source.read()?))
+        } else {
+            Ok(Err(source.read()?))
+        }
+    }
+}
+
 impl<'a> Decoder<'a> for &'a str {
     fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
         let buf = source.read_bytes()?;
-        str::from_utf8(buf).map_err(|_| Error::InvalidUtf8)
+        str::from_utf8(buf).map_err(|_| source.error(ErrorKind::InvalidUtf8))
     }
 }
 
@@ -121,60 +174,104 @@ impl Encoder for u8 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_byte(*self)
     }
+
+    fn encode_slice(items: &[Self], sink: &mut Sink) {
+        sink.write_bytes(items)
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(1)
+    }
 }
 
 impl Encoder for u16 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_u16(*self)
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(2)
+    }
 }
 
 impl Encoder for u32 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_u32(*self)
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(4)
+    }
 }
 
 impl Encoder for U128 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_bytes(&self.to_le_bytes())
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(16)
+    }
 }
 
 impl Encoder for I128 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_bytes(&self.to_le_bytes())
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(16)
+    }
 }
 
 impl Encoder for u64 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_u64(*self)
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(8)
+    }
 }
 
 impl Encoder for bool {
     fn encode(&self, sink: &mut Sink) {
         sink.write_bool(*self)
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(1)
+    }
 }
 
 impl Encoder for Address {
     fn encode(&self, sink: &mut Sink) {
         sink.write_bytes(self.as_ref())
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(20)
+    }
 }
 
 impl Encoder for H256 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_bytes(self.as_ref())
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(32)
+    }
 }
 
 impl<T: Encoder> Encoder for Vec<T> {
     fn encode(&self, sink: &mut Sink) {
         self.as_slice().encode(sink);
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        self.as_slice().encoded_len()
+    }
 }
 
 impl<T> Encoder for &[T]
@@ -183,10 +280,55 @@ where
 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_varuint(self.len() as u64);
-        for item in *self {
+        T::encode_slice(self, sink);
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        let items = self.iter().map(T::encoded_len).sum::<Option<usize>>()?;
+        Some(varuint_encode_size(self.len() as u64) + items)
+    }
+}
+
+impl<T: Encoder> Encoder for Box<T> {
+    fn encode(&self, sink: &mut Sink) {
+        self.as_ref().encode(sink)
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        self.as_ref().encoded_len()
+    }
+}
+
+impl<K: Encoder, V: Encoder> Encoder for BTreeMap<K, V> {
+    fn encode(&self, sink: &mut Sink) {
+        sink.write_varuint(self.len() as u64);
+        for (key, val) in self {
+            sink.write(key);
+            sink.write(val);
+        }
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        let entries = self
+            .iter()
+            .map(|(key, val)| Some(key.encoded_len()? + val.encoded_len()?))
+            .sum::<Option<usize>>()?;
+        Some(varuint_encode_size(self.len() as u64) + entries)
+    }
+}
+
+impl<T: Encoder> Encoder for BTreeSet<T> {
+    fn encode(&self, sink: &mut Sink) {
+        sink.write_varuint(self.len() as u64);
+        for item in self {
             sink.write(item);
         }
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        let items = self.iter().map(T::encoded_len).sum::<Option<usize>>()?;
+        Some(varuint_encode_size(self.len() as u64) + items)
+    }
 }
 
 impl<T: Encoder> Encoder for Option<T> {
@@ -198,6 +340,35 @@ impl<T: Encoder> Encoder for Option<T> {
             sink.write(false);
         }
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        match self {
+            Some(val) => Some(1 + val.encoded_len()?),
+            None => Some(1),
+        }
+    }
+}
+
+impl<T: Encoder, E: Encoder> Encoder for Result<T, E> {
+    fn encode(&self, sink: &mut Sink) {
+        match self {
+            Ok(val) => {
+                sink.write(true);
+                sink.write(val);
+            }
+            Err(err) => {
+                sink.write(false);
+                sink.write(err);
+            }
+        }
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        match self {
+            Ok(val) => Some(1 + val.encoded_len()?),
+            Err(err) => Some(1 + err.encoded_len()?),
+        }
+    }
 }
 
 impl Encoder for &str {
@@ -205,31 +376,49 @@ impl Encoder for &str {
         sink.write_varuint(self.len() as u64);
         sink.write_bytes(self.as_bytes());
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(varuint_encode_size(self.len() as u64) + self.len())
+    }
 }
 
 impl Encoder for String {
     fn encode(&self, sink: &mut Sink) {
         self.as_str().encode(sink)
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        self.as_str().encoded_len()
+    }
 }
 
 impl<T: Encoder> Encoder for &T {
     fn encode(&self, sink: &mut Sink) {
         (*self).encode(sink)
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        (*self).encoded_len()
+    }
 }
 
 impl<T: Encoder> Encoder for &mut T {
     fn encode(&self, sink: &mut Sink) {
         (*self as &T).encode(sink)
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        (*self as &T).encoded_len()
+    }
 }
 
 impl<T: Encoder, const N: usize> Encoder for [T; N] {
     fn encode(&self, sink: &mut Sink) {
-        for val in self {
-            sink.write(val)
-        }
+        T::encode_slice(self, sink)
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        self.iter().map(T::encoded_len).sum()
     }
 }
 
@@ -243,6 +432,84 @@ impl<'a, T: Decoder<'a> + Default + Copy, const N: usize> Decoder<'a> for [T; N]
     }
 }
 
+impl Encoder for U256 {
+    fn encode(&self, sink: &mut Sink) {
+        sink.write_bytes(&self.to_le_bytes())
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(32)
+    }
+}
+
+impl<'a> Decoder<'a> for U256 {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        source.read_u256()
+    }
+}
+
+//fixed size little endian encoding of the integer primitives without a dedicated `Sink` method
+macro_rules! impl_int_codec {
+    ($($ty:ty),*) => {
+        $(
+            impl Encoder for $ty {
+                fn encode(&self, sink: &mut Sink) {
+                    sink.write_bytes(&self.to_le_bytes())
+                }
+
+                fn encoded_len(&self) -> Option<usize> {
+                    Some(core::mem::size_of::<$ty>())
+                }
+            }
+
+            impl<'a> Decoder<'a> for $ty {
+                fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+                    let mut buf = [0; core::mem::size_of::<$ty>()];
+                    source.read_into(&mut buf)?;
+                    Ok(<$ty>::from_le_bytes(buf))
+                }
+            }
+        )*
+    };
+}
+
+impl_int_codec!(i8, i16, i32, i64, u128, i128);
+
+//pointer sized integers are encoded with 8 bytes so that the encoding does not depend on the platform
+impl Encoder for usize {
+    fn encode(&self, sink: &mut Sink) {
+        sink.write_u64(*self as u64)
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(8)
+    }
+}
+
+impl<'a> Decoder<'a> for usize {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        let val = source.read_u64()?;
+        core::convert::TryFrom::try_from(val).map_err(|_| source.error(ErrorKind::IrregularData))
+    }
+}
+
+impl Encoder for isize {
+    fn encode(&self, sink: &mut Sink) {
+        (*self as i64).encode(sink)
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(8)
+    }
+}
+
+impl<'a> Decoder<'a> for isize {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        let val: i64 = source.read()?;
+        core::convert::TryFrom::try_from(val).map_err(|_| source.error(ErrorKind::IrregularData))
+    }
+}
+
 /// reference:
 /// 1. https://github.com/rust-lang/rust/issues/24830
 /// 2. https://github.com/rust-lang/rust/blob/8f5b5f94dcdb9884737dfbc8efd893d1d70f0b14/src/libcore/hash/mod.rs#L239
@@ -278,6 +545,12 @@ for_each_tuple! {
                 let ($($item,)*) = self;
                 $(_sink.write($item);)*
             }
+
+            fn encoded_len(&self) -> Option<usize> {
+                #[allow(non_snake_case)]
+                let ($($item,)*) = self;
+                Some(0 $(+ $item.encoded_len()?)*)
+            }
         }
     }
 }
@@ -289,28 +562,28 @@ for_each_tuple! {
             fn deserialize(_parser: &mut VmValueParser<'a>) -> Result<Self, Error> {
                 let ty = _parser.source.read_byte()?;
                 if ty != crate::abi::event_builder::TYPE_LIST {
-                     return Err(Error::TypeInconsistency);
+                     return Err(_parser.source.error(ErrorKind::TypeInconsistency));
                 }
                 #[allow(unused_mut)]
                 let mut count = 0u32;
                 $(let _ :$item; count +=1;)*
                 let l = _parser.source.read_u32()?;
                 if l!= count {
-                    return Err(Error::LengthInconsistency);
+                    return Err(_parser.source.error(ErrorKind::LengthInconsistency));
                 }
                 Ok(($(_parser.read::<$item>()?,)*))
             }
         }
         impl<$($item: VmValueEncoder),*> VmValueEncoder for ($($item,)*) {
             fn serialize(&self, _builder: &mut VmValueBuilder) {
-                _builder.common.sink.write_byte(crate::abi::event_builder::TYPE_LIST);
-                 #[allow(unused_mut)]
+                #[allow(unused_mut)]
                 let mut count = 0u32;
                 #[allow(non_snake_case)]
                 let ($($item,)*) = self;
                 $(let _ = $item;count +=1;)*
-                _builder.common.sink.write_u32(count);
-                $(_builder.write($item);)*
+                _builder.list_of(count, |_builder| {
+                    $(_builder.write($item);)*
+                });
             }
         }
     }
@@ -325,3 +598,189 @@ fn test_array() {
     let addrs2: [Address; 2] = Source::new(buf.as_slice()).read().unwrap();
     assert_eq!(addrs, addrs2);
 }
+
+#[test]
+fn test_bytes() {
+    let data: Vec<u8> = (0..=255).collect();
+    let mut sink = Sink::new(10);
+    sink.write(&data);
+    sink.write([1u8, 2, 3]);
+    let buf = sink.into();
+    assert_eq!(buf.len(), 3 + 256 + 3);
+    let mut source = Source::new(&buf);
+    let data2: Vec<u8> = source.read().unwrap();
+    assert_eq!(data, data2);
+    let arr: [u8; 3] = source.read().unwrap();
+    assert_eq!(arr, [1, 2, 3]);
+
+    let mut source = Source::new(&buf[..100]);
+    assert!(source.read::<Vec<u8>>().is_err());
+}
+
+#[test]
+fn test_collections() {
+    let mut map = BTreeMap::new();
+    map.insert("b".to_string(), U128::new(2));
+    map.insert("a".to_string(), U128::new(1));
+    let set: BTreeSet<u32> = [3, 1, 2].iter().cloned().collect();
+    let boxed = Box::new(Address::repeat_byte(1));
+    let mut sink = Sink::new(10);
+    sink.write(&map);
+    sink.write(&set);
+    sink.write(&boxed);
+    let buf = sink.into();
+    let mut source = Source::new(&buf);
+    let map2: BTreeMap<String, U128> = source.read().unwrap();
+    let set2: BTreeSet<u32> = source.read().unwrap();
+    let boxed2: Box<Address> = source.read().unwrap();
+    assert_eq!(map, map2);
+    assert_eq!(set, set2);
+    assert_eq!(boxed, boxed2);
+
+    let mut sink = Sink::new(10);
+    sink.write(&set);
+    let buf = sink.into();
+    let items: Vec<u32> = Source::new(&buf).read().unwrap();
+    assert_eq!(items, vec![1, 2, 3]);
+
+    let mut sink = Sink::new(10);
+    sink.write(vec![2u32, 1]);
+    let buf = sink.into();
+    assert!(Source::new(&buf).read::<BTreeSet<u32>>().is_err());
+    let mut sink = Sink::new(10);
+    sink.write(vec![(1u32, true), (1u32, false)]);
+    let buf = sink.into();
+    assert!(Source::new(&buf).read::<BTreeMap<u32, bool>>().is_err());
+}
+
+#[test]
+fn test_integers() {
+    let mut sink = Sink::new(10);
+    sink.write(-1i8);
+    sink.write(-2i16);
+    sink.write(-3i32);
+    sink.write(i64::MIN);
+    sink.write(u128::MAX);
+    sink.write(-5i128);
+    sink.write(7usize);
+    sink.write(-8isize);
+    sink.write(U256::MAX);
+    let buf = sink.into();
+    assert_eq!(buf.len(), 1 + 2 + 4 + 8 + 16 + 16 + 8 + 8 + 32);
+    assert_eq!(&buf[1..3], &[0xfe, 0xff]);
+    let mut source = Source::new(&buf);
+    assert_eq!(source.read::<i8>().unwrap(), -1);
+    assert_eq!(source.read::<i16>().unwrap(), -2);
+    assert_eq!(source.read::<i32>().unwrap(), -3);
+    assert_eq!(source.read::<i64>().unwrap(), i64::MIN);
+    assert_eq!(source.read::<u128>().unwrap(), u128::MAX);
+    assert_eq!(source.read::<i128>().unwrap(), -5);
+    assert_eq!(source.read::<usize>().unwrap(), 7);
+    assert_eq!(source.read::<isize>().unwrap(), -8);
+    assert_eq!(source.read::<U256>().unwrap(), U256::MAX);
+    assert!(source.read::<i8>().is_err());
+
+    let mut sink = Sink::new(10);
+    sink.write(U128::new(1000));
+    sink.write(I128::new(-1000));
+    let buf = sink.into();
+    let mut source = Source::new(&buf);
+    assert_eq!(source.read::<u128>().unwrap(), 1000);
+    assert_eq!(source.read::<i128>().unwrap(), -1000);
+}
+
+#[test]
+fn test_varuint() {
+    for &val in [0u64, 0xfc, 0xfd, 0xffff, 0x10000, 0xffff_ffff, 0x1_0000_0000, u64::MAX].iter() {
+        let mut sink = Sink::new(10);
+        sink.write_varuint(val);
+        let buf = sink.into();
+        assert_eq!(buf.len(), super::source::varuint_encode_size(val));
+        assert_eq!(Source::new(&buf).read_varuint().unwrap(), val);
+    }
+    let buf = [0xfd, 0x10, 0x00];
+    assert!(
+        matches!(Source::new(&buf).read_varuint(), Err(ref e) if e.kind() == ErrorKind::NonCanonicalVarUint)
+    );
+}
+
+#[test]
+fn test_strict() {
+    let mut sink = Sink::new(10);
+    sink.write("abc");
+    sink.write(1u8);
+    let buf = sink.into();
+    let mut source = Source::new(&buf);
+    assert_eq!(source.read::<&str>().unwrap(), "abc");
+    assert!(source.finish().is_ok());
+    let mut source = Source::strict(&buf);
+    assert_eq!(source.read::<&str>().unwrap(), "abc");
+    assert!(matches!(source.finish(), Err(ref e) if e.kind() == ErrorKind::TrailingBytes));
+    source.read_byte().unwrap();
+    assert!(source.finish().is_ok());
+
+    let buf = [0xfe, 0xff, 0xff, 0xff, 0x00, 1, 2, 3];
+    assert!(
+        matches!(Source::new(&buf).read::<Vec<u32>>(), Err(ref e) if e.kind() == ErrorKind::UnexpectedEOF)
+    );
+    assert!(
+        matches!(Source::strict(&buf).read::<Vec<u32>>(), Err(ref e) if e.kind() == ErrorKind::LengthInconsistency)
+    );
+    assert!(
+        matches!(Source::strict(&buf).read_bytes(), Err(ref e) if e.kind() == ErrorKind::LengthInconsistency)
+    );
+
+    let buf = [19, 0, 0];
+    assert!(
+        matches!(Source::new(&buf).read_native_address(), Err(ref e) if e.kind() == ErrorKind::LengthInconsistency)
+    );
+}
+
+#[test]
+fn test_encoded_len() {
+    fn check<T: Encoder>(val: T) {
+        let mut sink = Sink::new(0);
+        sink.write(&val);
+        assert_eq!(val.encoded_len(), Some(sink.bytes().len()));
+    }
+    check(1u8);
+    check(2u16);
+    check(3u32);
+    check(4u64);
+    check(-5i8);
+    check(-6i128);
+    check(7usize);
+    check(true);
+    check(U128::new(8));
+    check(I128::new(-9));
+    check(U256::MAX);
+    check(Address::repeat_byte(1));
+    check(H256::zero());
+    check("a".repeat(300));
+    check(vec![0u8; 70000]);
+    check(vec![Address::zero(); 3]);
+    check(&[1u32, 2, 3][..]);
+    check([U128::new(1); 4]);
+    check(Some(vec![1u64]));
+    check(None::<u64>);
+    check(Ok::<u32, String>(1));
+    check(Err::<u32, String>("err".to_string()));
+    check(Box::new((1u8, "b", Address::zero())));
+    check(());
+    let map: BTreeMap<String, Vec<u32>> = vec![("a".to_string(), vec![1, 2])].into_iter().collect();
+    check(&map);
+    let set: BTreeSet<u64> = (0..300).collect();
+    check(&set);
+    check(crate::abi::DispatchError::unsupported_method("foo"));
+
+    struct Unsized;
+    impl Encoder for Unsized {
+        fn encode(&self, sink: &mut Sink) {
+            sink.write_byte(0)
+        }
+    }
+    assert_eq!(Unsized.encoded_len(), None);
+    assert_eq!((1u32, Unsized).encoded_len(), None);
+    assert_eq!(vec![Unsized].encoded_len(), None);
+    assert_eq!(Vec::<Unsized>::new().encoded_len(), Some(1));
+}
@@ -0,0 +1,128 @@
+use super::{Decoder, Encoder, Error, Sink, Source};
+use crate::prelude::*;
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+///Error type returned by `#[contract]` methods, the generated dispatcher encodes it as a `DispatchError`.
+///
+///# Example
+///```
+/// # use ontio_std::abi::ContractError;
+///   enum TokenError {
+///       InsufficientBalance,
+///   }
+///   impl ContractError for TokenError {
+///       fn code(&self) -> u32 {
+///           1000
+///       }
+///       fn message(&self) -> String {
+///           "insufficient balance".to_string()
+///       }
+///   }
+///```
+pub trait ContractError {
+    ///Error code, codes below `DispatchError::RESERVED_CODE_MAX` are reserved by the dispatcher.
+    fn code(&self) -> u32;
+
+    fn message(&self) -> String {
+        String::new()
+    }
+}
+
+///Structured error produced by the dispatcher, which off-chain clients and calling contracts can decode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchError {
+    pub code: u32,
+    pub message: String,
+}
+
+impl DispatchError {
+    ///The invoked method is not defined by the contract.
+    pub const UNSUPPORTED_METHOD: u32 = 1;
+    ///The method name or one of the arguments can not be decoded.
+    pub const INVALID_ARGUMENT: u32 = 2;
+    ///Codes up to this value are reserved for errors raised by the dispatcher itself.
+    pub const RESERVED_CODE_MAX: u32 = 255;
+
+    pub fn new(code: u32, message: &str) -> Self {
+        DispatchError { code, message: message.to_string() }
+    }
+
+    pub fn from_error<E: ContractError>(err: &E) -> Self {
+        DispatchError { code: err.code(), message: err.message() }
+    }
+
+    pub fn unsupported_method(method: &str) -> Self {
+        DispatchError {
+            code: Self::UNSUPPORTED_METHOD,
+            message: format!("invoke unsupported method: {}", method),
+        }
+    }
+
+    pub fn invalid_argument(name: &str) -> Self {
+        DispatchError {
+            code: Self::INVALID_ARGUMENT,
+            message: format!("failed to decode argument: {}", name),
+        }
+    }
+
+    ///Same as `invalid_argument`, with the position and type reported by the decoding error.
+    pub fn decode_failed(name: &str, err: &Error) -> Self {
+        DispatchError {
+            code: Self::INVALID_ARGUMENT,
+            message: format!("failed to decode argument: {}, {}", name, err),
+        }
+    }
+}
+
+impl ContractError for DispatchError {
+    fn code(&self) -> u32 {
+        self.code
+    }
+
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+impl Display for DispatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "dispatch error {}: {}", self.code, self.message)
+    }
+}
+
+impl Encoder for DispatchError {
+    fn encode(&self, sink: &mut Sink) {
+        sink.write(self.code);
+        sink.write(&self.message);
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Some(self.code.encoded_len()? + self.message.encoded_len()?)
+    }
+}
+
+impl<'a> Decoder<'a> for DispatchError {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        let code = source.read()?;
+        let message = source.read()?;
+        Ok(DispatchError { code, message })
+    }
+}
+
+#[test]
+fn test_dispatch_error_codec() {
+    let err = DispatchError::invalid_argument("owner");
+    let mut sink = Sink::new(16);
+    sink.write(&err);
+    let buf = sink.into();
+    let err2: DispatchError = Source::new(&buf).read().unwrap();
+    assert_eq!(err, err2);
+    assert_eq!(err2.code, DispatchError::INVALID_ARGUMENT);
+
+    let res: Result<u32, DispatchError> = Err(DispatchError::unsupported_method("foo"));
+    let mut sink = Sink::new(16);
+    sink.write(&res);
+    let buf = sink.into();
+    let res2: Result<u32, DispatchError> = Source::new(&buf).read().unwrap();
+    assert_eq!(res, res2);
+}
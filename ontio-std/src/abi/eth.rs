@@ -0,0 +1,659 @@
+//! Solidity ABI encoding, used to build the input of `contract::eth::evm_invoke` and read its output.
+//!
+//! Every value takes one or more 32 bytes words. Static values are written in place, while dynamic
+//! values (`bytes`, `string`, dynamic arrays and tuples containing them) are written after all the
+//! items of the enclosing tuple, their place holding the offset of the data from the tuple start.
+//!
+//! | Solidity            | Rust                                                   |
+//! |---------------------|--------------------------------------------------------|
+//! | `uint<N>`           | `u8`, `u16`, `u32`, `u64`, `u128`, `U128`, `U256`      |
+//! | `int<N>`            | `i8`, `i16`, `i32`, `i64`, `i128`, `I128`              |
+//! | `address`           | `Address`                                              |
+//! | `bool`              | `bool`                                                 |
+//! | `bytes`             | `&[u8]`, `Vec<u8>`                                     |
+//! | `bytes<N>`          | `[u8; N]` with `0 < N <= 32`, `H256` for `bytes32`     |
+//! | `string`            | `&str`, `String`                                       |
+//! | `T[]`               | `&[T]`, `Vec<T>`, except `uint8[]` which is `bytes`    |
+//! | `(T1, T2, ...)`     | `(T1, T2, ...)`                                        |
+//!
+//! # Example
+//!```no_run
+//! # use ontio_std::abi::eth;
+//! # use ontio_std::contract::eth::evm_invoke;
+//! # use ontio_std::macros::keccak256;
+//! # use ontio_std::prelude::*;
+//! # use ontio_std::types::U256;
+//! # let (caller, token, owner) = (Address::zero(), Address::zero(), Address::zero());
+//!   let input = eth::encode_call(&keccak256!("balanceOf(address)"), (&owner,));
+//!   let output = evm_invoke(&caller, &token, &input);
+//!   let (balance,): (U256,) = eth::decode(&output).unwrap();
+//!```
+//!
+//! Byte arrays of other lengths are not a solidity type and fail to build:
+//!```compile_fail
+//! # use ontio_std::abi::eth;
+//!   let input = eth::encode(([0u8; 33],));
+//!```
+use super::{Error, ErrorKind, Sink};
+use crate::prelude::*;
+use crate::types::U256;
+use core::convert::TryInto;
+
+const WORD: usize = 32;
+
+///Encode the input of a call, `selector` is the keccak256 hash of the method signature
+///of which the first 4 bytes are kept, and `params` the tuple of arguments.
+pub fn encode_call<T: EthEncoder>(selector: &H256, params: T) -> Vec<u8> {
+    let mut sink = Sink::new(4 + T::HEAD_LEN);
+    sink.write_bytes(&selector.as_bytes()[..4]);
+    params.encode(&mut sink);
+    sink.into()
+}
+
+///Encode a tuple of values, as the arguments of a call without the selector.
+pub fn encode<T: EthEncoder>(params: T) -> Vec<u8> {
+    let mut sink = Sink::new(T::HEAD_LEN);
+    params.encode(&mut sink);
+    sink.into()
+}
+
+///Decode a tuple of values, such as the return values of a call.
+pub fn decode<'a, T: EthDecoder<'a>>(data: &'a [u8]) -> Result<T, Error> {
+    T::decode(data, 0)
+}
+
+pub trait EthEncoder {
+    ///Whether the value is written after the items of the enclosing tuple.
+    const DYNAMIC: bool;
+    ///Size of the value in the items of the enclosing tuple, one word for dynamic values.
+    const HEAD_LEN: usize = WORD;
+
+    fn encode(&self, sink: &mut Sink);
+
+    #[doc(hidden)]
+    fn encode_slice(items: &[Self], sink: &mut Sink)
+    where
+        Self: Sized,
+    {
+        write_usize(sink, items.len());
+        let mut tuple = TupleEncoder::new(sink, items.len() * Self::HEAD_LEN);
+        for item in items {
+            tuple.item(item);
+        }
+        tuple.finish();
+    }
+}
+
+pub trait EthDecoder<'a>: Sized {
+    ///Whether the value is read after the items of the enclosing tuple.
+    const DYNAMIC: bool;
+    ///Size of the value in the items of the enclosing tuple, one word for dynamic values.
+    const HEAD_LEN: usize = WORD;
+
+    ///Decode the value starting at `pos` in `data`.
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error>;
+
+    #[doc(hidden)]
+    fn decode_vec(data: &'a [u8], pos: usize) -> Result<Vec<Self>, Error> {
+        let len = read_usize(data, pos)?;
+        //items without head, such as `()`, would let any length pass the check below
+        if Self::HEAD_LEN == 0 {
+            return Err(Error::new(ErrorKind::IrregularData, pos));
+        }
+        if len.checked_mul(Self::HEAD_LEN).map_or(true, |l| l > data.len() - pos - WORD) {
+            return Err(Error::new(ErrorKind::LengthInconsistency, pos));
+        }
+        let mut tuple = TupleDecoder::new(data, pos + WORD);
+        let mut items = Vec::with_capacity(cmp::min(len, 1024));
+        for _i in 0..len {
+            items.push(tuple.item()?);
+        }
+        Ok(items)
+    }
+}
+
+//writes the items of a tuple, dynamic items are appended after the heads of all items
+struct TupleEncoder<'s> {
+    sink: &'s mut Sink,
+    head_len: usize,
+    tail: Sink,
+}
+
+impl<'s> TupleEncoder<'s> {
+    fn new(sink: &'s mut Sink, head_len: usize) -> Self {
+        TupleEncoder { sink, head_len, tail: Sink::new(0) }
+    }
+
+    fn item<T: EthEncoder + ?Sized>(&mut self, item: &T) {
+        if T::DYNAMIC {
+            write_usize(self.sink, self.head_len + self.tail.bytes().len());
+            item.encode(&mut self.tail);
+        } else {
+            item.encode(self.sink);
+        }
+    }
+
+    fn finish(self) {
+        self.sink.write_bytes(self.tail.bytes());
+    }
+}
+
+//reads the items of a tuple starting at `base`, offsets of dynamic items are relative to it
+struct TupleDecoder<'a> {
+    data: &'a [u8],
+    base: usize,
+    head: usize,
+}
+
+impl<'a> TupleDecoder<'a> {
+    fn new(data: &'a [u8], base: usize) -> Self {
+        TupleDecoder { data, base, head: base }
+    }
+
+    fn item<T: EthDecoder<'a>>(&mut self) -> Result<T, Error> {
+        let pos = if T::DYNAMIC {
+            let offset = read_usize(self.data, self.head)?;
+            match self.base.checked_add(offset) {
+                Some(pos) if pos <= self.data.len() => pos,
+                _ => return Err(Error::new(ErrorKind::UnexpectedEOF, self.head)),
+            }
+        } else {
+            self.head
+        };
+        self.head += T::HEAD_LEN;
+        T::decode(self.data, pos).map_err(Error::with_type_name::<T>)
+    }
+}
+
+fn read_word(data: &[u8], pos: usize) -> Result<&[u8], Error> {
+    data.get(pos..pos + WORD).ok_or_else(|| Error::new(ErrorKind::UnexpectedEOF, pos))
+}
+
+//read a word holding a length or an offset, which must fit in usize
+fn read_usize(data: &[u8], pos: usize) -> Result<usize, Error> {
+    let word = read_word(data, pos)?;
+    let (high, low) = word.split_at(WORD - 8);
+    let val = u64::from_be_bytes(low.try_into().unwrap());
+    if high.iter().any(|&b| b != 0) || val > usize::MAX as u64 {
+        return Err(Error::new(ErrorKind::IrregularData, pos));
+    }
+    Ok(val as usize)
+}
+
+fn write_usize(sink: &mut Sink, val: usize) {
+    (val as u64).encode(sink);
+}
+
+//write `data` left aligned and padded with zeros to a multiple of the word size
+fn write_padded(sink: &mut Sink, data: &[u8]) {
+    sink.write_bytes(data);
+    let padding = (WORD - data.len() % WORD) % WORD;
+    sink.write_bytes(&[0u8; WORD][..padding]);
+}
+
+//read `len` bytes left aligned in the words starting at `pos`
+fn read_padded(data: &[u8], pos: usize, len: usize) -> Result<&[u8], Error> {
+    match pos.checked_add(len) {
+        Some(end) if end <= data.len() => Ok(&data[pos..end]),
+        _ => Err(Error::new(ErrorKind::LengthInconsistency, pos)),
+    }
+}
+
+//read the length prefixed content of `bytes` and `string`
+fn read_bytes(data: &[u8], pos: usize) -> Result<&[u8], Error> {
+    let len = read_usize(data, pos)?;
+    read_padded(data, pos + WORD, len)
+}
+
+macro_rules! impl_eth_uint {
+    ($($ty:ty),*) => {
+        $(
+            impl EthEncoder for $ty {
+                const DYNAMIC: bool = false;
+
+                fn encode(&self, sink: &mut Sink) {
+                    let bytes = self.to_be_bytes();
+                    sink.write_bytes(&[0u8; WORD][..WORD - bytes.len()]);
+                    sink.write_bytes(&bytes);
+                }
+            }
+
+            impl<'a> EthDecoder<'a> for $ty {
+                const DYNAMIC: bool = false;
+
+                fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+                    const SIZE: usize = core::mem::size_of::<$ty>();
+                    let word = read_word(data, pos)?;
+                    if word[..WORD - SIZE].iter().any(|&b| b != 0) {
+                        return Err(Error::new(ErrorKind::IrregularData, pos));
+                    }
+                    Ok(<$ty>::from_be_bytes(word[WORD - SIZE..].try_into().unwrap()))
+                }
+            }
+        )*
+    };
+}
+
+impl_eth_uint!(u16, u32, u64, u128);
+
+macro_rules! impl_eth_int {
+    ($($ty:ty),*) => {
+        $(
+            impl EthEncoder for $ty {
+                const DYNAMIC: bool = false;
+
+                fn encode(&self, sink: &mut Sink) {
+                    let bytes = self.to_be_bytes();
+                    let sign = if *self < 0 { 0xff } else { 0 };
+                    sink.write_bytes(&[sign; WORD][..WORD - bytes.len()]);
+                    sink.write_bytes(&bytes);
+                }
+            }
+
+            impl<'a> EthDecoder<'a> for $ty {
+                const DYNAMIC: bool = false;
+
+                fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+                    const SIZE: usize = core::mem::size_of::<$ty>();
+                    let word = read_word(data, pos)?;
+                    let val = <$ty>::from_be_bytes(word[WORD - SIZE..].try_into().unwrap());
+                    //the value must be sign extended
+                    let sign = if val < 0 { 0xff } else { 0 };
+                    if word[..WORD - SIZE].iter().any(|&b| b != sign) {
+                        return Err(Error::new(ErrorKind::IrregularData, pos));
+                    }
+                    Ok(val)
+                }
+            }
+        )*
+    };
+}
+
+impl_eth_int!(i8, i16, i32, i64, i128);
+
+//u8 is implemented apart so that `[u8]` is written as `bytes` rather than `uint8[]`
+impl EthEncoder for u8 {
+    const DYNAMIC: bool = false;
+
+    fn encode(&self, sink: &mut Sink) {
+        (*self as u16).encode(sink);
+    }
+
+    fn encode_slice(items: &[Self], sink: &mut Sink) {
+        write_usize(sink, items.len());
+        write_padded(sink, items);
+    }
+}
+
+impl<'a> EthDecoder<'a> for u8 {
+    const DYNAMIC: bool = false;
+
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+        let val = u16::decode(data, pos)?;
+        if val > u8::MAX as u16 {
+            return Err(Error::new(ErrorKind::IrregularData, pos));
+        }
+        Ok(val as u8)
+    }
+
+    fn decode_vec(data: &'a [u8], pos: usize) -> Result<Vec<Self>, Error> {
+        read_bytes(data, pos).map(|bytes| bytes.to_vec())
+    }
+}
+
+impl EthEncoder for U128 {
+    const DYNAMIC: bool = false;
+
+    fn encode(&self, sink: &mut Sink) {
+        self.raw().encode(sink);
+    }
+}
+
+impl<'a> EthDecoder<'a> for U128 {
+    const DYNAMIC: bool = false;
+
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+        u128::decode(data, pos).map(U128::new)
+    }
+}
+
+impl EthEncoder for I128 {
+    const DYNAMIC: bool = false;
+
+    fn encode(&self, sink: &mut Sink) {
+        self.raw().encode(sink);
+    }
+}
+
+impl<'a> EthDecoder<'a> for I128 {
+    const DYNAMIC: bool = false;
+
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+        i128::decode(data, pos).map(I128::new)
+    }
+}
+
+impl EthEncoder for U256 {
+    const DYNAMIC: bool = false;
+
+    fn encode(&self, sink: &mut Sink) {
+        sink.write_bytes(&self.to_be_bytes());
+    }
+}
+
+impl<'a> EthDecoder<'a> for U256 {
+    const DYNAMIC: bool = false;
+
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+        read_word(data, pos).map(U256::from_big_endian)
+    }
+}
+
+impl EthEncoder for bool {
+    const DYNAMIC: bool = false;
+
+    fn encode(&self, sink: &mut Sink) {
+        (*self as u16).encode(sink);
+    }
+}
+
+impl<'a> EthDecoder<'a> for bool {
+    const DYNAMIC: bool = false;
+
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+        match u16::decode(data, pos)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::new(ErrorKind::IrregularData, pos)),
+        }
+    }
+}
+
+impl EthEncoder for Address {
+    const DYNAMIC: bool = false;
+
+    fn encode(&self, sink: &mut Sink) {
+        sink.write_bytes(&[0u8; WORD - 20]);
+        sink.write_bytes(self.as_bytes());
+    }
+}
+
+impl<'a> EthDecoder<'a> for Address {
+    const DYNAMIC: bool = false;
+
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+        let word = read_word(data, pos)?;
+        if word[..WORD - 20].iter().any(|&b| b != 0) {
+            return Err(Error::new(ErrorKind::IrregularData, pos));
+        }
+        Ok(Address::from_slice(&word[WORD - 20..]))
+    }
+}
+
+impl EthEncoder for H256 {
+    const DYNAMIC: bool = false;
+
+    fn encode(&self, sink: &mut Sink) {
+        sink.write_bytes(self.as_bytes());
+    }
+}
+
+impl<'a> EthDecoder<'a> for H256 {
+    const DYNAMIC: bool = false;
+
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+        read_word(data, pos).map(H256::from_slice)
+    }
+}
+
+//rejects at compile time the byte arrays which are not a solidity `bytes<N>` type
+struct BytesLen<const N: usize>;
+
+impl<const N: usize> BytesLen<N> {
+    const VALID: () =
+        assert!(N > 0 && N <= WORD, "byte array length is not a solidity bytes<N> type");
+}
+
+impl<const N: usize> EthEncoder for [u8; N] {
+    const DYNAMIC: bool = false;
+
+    fn encode(&self, sink: &mut Sink) {
+        let () = BytesLen::<N>::VALID;
+        write_padded(sink, self);
+    }
+}
+
+impl<'a, const N: usize> EthDecoder<'a> for [u8; N] {
+    const DYNAMIC: bool = false;
+
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+        let () = BytesLen::<N>::VALID;
+        let word = read_word(data, pos)?;
+        if word[N..].iter().any(|&b| b != 0) {
+            return Err(Error::new(ErrorKind::IrregularData, pos));
+        }
+        Ok(word[..N].try_into().unwrap())
+    }
+}
+
+impl EthEncoder for str {
+    const DYNAMIC: bool = true;
+
+    fn encode(&self, sink: &mut Sink) {
+        u8::encode_slice(self.as_bytes(), sink);
+    }
+}
+
+impl<'a> EthDecoder<'a> for &'a str {
+    const DYNAMIC: bool = true;
+
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+        let bytes = read_bytes(data, pos)?;
+        str::from_utf8(bytes).map_err(|_| Error::new(ErrorKind::InvalidUtf8, pos))
+    }
+}
+
+impl EthEncoder for String {
+    const DYNAMIC: bool = true;
+
+    fn encode(&self, sink: &mut Sink) {
+        self.as_str().encode(sink);
+    }
+}
+
+impl<'a> EthDecoder<'a> for String {
+    const DYNAMIC: bool = true;
+
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+        <&str>::decode(data, pos).map(|s| s.to_string())
+    }
+}
+
+impl<T: EthEncoder> EthEncoder for [T] {
+    const DYNAMIC: bool = true;
+
+    fn encode(&self, sink: &mut Sink) {
+        T::encode_slice(self, sink);
+    }
+}
+
+impl<'a> EthDecoder<'a> for &'a [u8] {
+    const DYNAMIC: bool = true;
+
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+        read_bytes(data, pos)
+    }
+}
+
+impl<T: EthEncoder> EthEncoder for Vec<T> {
+    const DYNAMIC: bool = true;
+
+    fn encode(&self, sink: &mut Sink) {
+        T::encode_slice(self, sink);
+    }
+}
+
+impl<'a, T: EthDecoder<'a>> EthDecoder<'a> for Vec<T> {
+    const DYNAMIC: bool = true;
+
+    fn decode(data: &'a [u8], pos: usize) -> Result<Self, Error> {
+        T::decode_vec(data, pos)
+    }
+}
+
+impl<T: EthEncoder + ?Sized> EthEncoder for &T {
+    const DYNAMIC: bool = T::DYNAMIC;
+    const HEAD_LEN: usize = T::HEAD_LEN;
+
+    fn encode(&self, sink: &mut Sink) {
+        (*self).encode(sink);
+    }
+}
+
+macro_rules! impl_eth_tuple {
+    ($($item:ident)*) => {
+        impl<$($item: EthEncoder),*> EthEncoder for ($($item,)*) {
+            const DYNAMIC: bool = false $(|| $item::DYNAMIC)*;
+            const HEAD_LEN: usize = if <Self as EthEncoder>::DYNAMIC { WORD } else { 0 $(+ $item::HEAD_LEN)* };
+
+            fn encode(&self, _sink: &mut Sink) {
+                #[allow(non_snake_case)]
+                let ($($item,)*) = self;
+                #[allow(unused_mut)]
+                let mut _tuple = TupleEncoder::new(_sink, 0 $(+ <$item as EthEncoder>::HEAD_LEN)*);
+                $(_tuple.item($item);)*
+                _tuple.finish();
+            }
+        }
+
+        impl<'a, $($item: EthDecoder<'a>),*> EthDecoder<'a> for ($($item,)*) {
+            const DYNAMIC: bool = false $(|| $item::DYNAMIC)*;
+            const HEAD_LEN: usize = if <Self as EthDecoder<'a>>::DYNAMIC { WORD } else { 0 $(+ $item::HEAD_LEN)* };
+
+            fn decode(_data: &'a [u8], pos: usize) -> Result<Self, Error> {
+                #[allow(unused_mut)]
+                let mut _tuple = TupleDecoder::new(_data, pos);
+                Ok(($(_tuple.item::<$item>()?,)*))
+            }
+        }
+    };
+}
+
+impl_eth_tuple!();
+impl_eth_tuple!(A);
+impl_eth_tuple!(A B);
+impl_eth_tuple!(A B C);
+impl_eth_tuple!(A B C D);
+impl_eth_tuple!(A B C D E);
+impl_eth_tuple!(A B C D E F);
+impl_eth_tuple!(A B C D E F G);
+impl_eth_tuple!(A B C D E F G H);
+impl_eth_tuple!(A B C D E F G H I);
+impl_eth_tuple!(A B C D E F G H I J);
+impl_eth_tuple!(A B C D E F G H I J K);
+impl_eth_tuple!(A B C D E F G H I J K L);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::macros::keccak256;
+
+    fn hex(words: &[&str]) -> Vec<u8> {
+        let s: String = words.concat();
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_encode_call() {
+        let to = Address::repeat_byte(1);
+        let input =
+            encode_call(&keccak256!("transfer(address,uint256)"), (&to, U256::from(1000u128)));
+        let expected = hex(&[
+            "a9059cbb",
+            "0000000000000000000000000101010101010101010101010101010101010101",
+            "00000000000000000000000000000000000000000000000000000000000003e8",
+        ]);
+        assert_eq!(input, expected);
+
+        //example of the solidity documentation
+        let params =
+            (U256::from(0x123u128), vec![0x456u32, 0x789], *b"1234567890", &b"Hello, world!"[..]);
+        let input = encode_call(&keccak256!("f(uint256,uint32[],bytes10,bytes)"), params);
+        let expected = hex(&[
+            "8be65246",
+            "0000000000000000000000000000000000000000000000000000000000000123",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "3132333435363738393000000000000000000000000000000000000000000000",
+            "00000000000000000000000000000000000000000000000000000000000000e0",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000456",
+            "0000000000000000000000000000000000000000000000000000000000000789",
+            "000000000000000000000000000000000000000000000000000000000000000d",
+            "48656c6c6f2c20776f726c642100000000000000000000000000000000000000",
+        ]);
+        assert_eq!(input, expected);
+        let res: (U256, Vec<u32>, [u8; 10], &[u8]) = decode(&input[4..]).unwrap();
+        assert_eq!(res.0, U256::from(0x123u128));
+        assert_eq!(res.1, vec![0x456, 0x789]);
+        assert_eq!(&res.2, b"1234567890");
+        assert_eq!(res.3, b"Hello, world!");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let nested = vec![vec![1u64, 2], vec![], vec![3]];
+        let names = vec!["one".to_string(), "two".to_string()];
+        let params = (
+            &nested,
+            &names,
+            (true, "inner", -1i64),
+            I128::new(-200),
+            H256::repeat_byte(7),
+            Address::repeat_byte(8),
+            u8::MAX,
+        );
+        let data = encode(params);
+        #[allow(clippy::type_complexity)]
+        let res: (Vec<Vec<u64>>, Vec<String>, (bool, &str, i64), I128, H256, Address, u8) =
+            decode(&data).unwrap();
+        assert_eq!(res.0, nested);
+        assert_eq!(res.1, names);
+        assert_eq!(res.2, (true, "inner", -1));
+        assert_eq!(res.3.raw(), -200);
+        assert_eq!(res.4, H256::repeat_byte(7));
+        assert_eq!(res.5, Address::repeat_byte(8));
+        assert_eq!(res.6, u8::MAX);
+        assert_eq!(&data[3 * 32..4 * 32 - 1], &[0xff; 31][..]);
+    }
+
+    #[test]
+    fn test_decode_error() {
+        let data = encode((U256::from(u32::MAX as u128 + 1),));
+        let err = decode::<(u32,)>(&data).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::IrregularData);
+        assert_eq!(err.position(), 0);
+        #[cfg(feature = "error-type-names")]
+        assert_eq!(err.type_name(), Some("u32"));
+        assert_eq!(
+            decode::<(i64,)>(&encode((u64::MAX,))).unwrap_err().kind(),
+            ErrorKind::IrregularData
+        );
+        assert_eq!(
+            decode::<(bool,)>(&encode((2u8,))).unwrap_err().kind(),
+            ErrorKind::IrregularData
+        );
+        assert_eq!(decode::<(u64, u64)>(&data).unwrap_err().kind(), ErrorKind::UnexpectedEOF);
+
+        let mut data = encode(("text",));
+        data[63] = 33; //string length
+        assert_eq!(decode::<(String,)>(&data).unwrap_err().kind(), ErrorKind::LengthInconsistency);
+        data[31] = 0x80; //offset
+        let err = decode::<(String,)>(&data).unwrap_err();
+        assert_eq!((err.kind(), err.position()), (ErrorKind::UnexpectedEOF, 0));
+
+        let mut data = encode((Vec::<()>::new(),));
+        data[63] = 0xff; //array length
+        let err = decode::<(Vec<()>,)>(&data).unwrap_err();
+        assert_eq!((err.kind(), err.position()), (ErrorKind::IrregularData, 32));
+    }
+}
@@ -1,39 +1,182 @@
 mod codec;
+mod contract_error;
+pub mod eth;
 mod sink;
 mod source;
 mod vm_value_builder;
 mod vm_value_codec;
 
+pub use self::contract_error::{ContractError, DispatchError};
 pub use self::sink::Sink;
 pub use self::source::Source;
 use crate::prelude::*;
+use core::fmt::{Display, Formatter, Result as FmtResult};
 pub(crate) mod event_builder;
 pub use event_builder::EventBuilder;
 pub(crate) use event_builder::VmValueBuilderCommon;
-pub use vm_value_builder::{VmValueBuilder, VmValueParser};
+pub use vm_value_builder::{VmValueBuilder, VmValueParser, MAX_VALUE_DEPTH};
+pub use vm_value_codec::VmValue;
 pub use vm_value_codec::VmValueDecoder;
 pub use vm_value_codec::VmValueEncoder;
 
 pub use ontio_derive_codec::*;
 
-#[derive(Debug)]
-pub enum Error {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
     UnexpectedEOF,
     IrregularData,
     InvalidUtf8,
     TypeInconsistency,
     LengthInconsistency,
+    TrailingBytes,
+    NonCanonicalVarUint,
+    NestingTooDeep,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let desc = match self {
+            ErrorKind::UnexpectedEOF => "unexpected end of data",
+            ErrorKind::IrregularData => "irregular data",
+            ErrorKind::InvalidUtf8 => "invalid utf8 string",
+            ErrorKind::TypeInconsistency => "inconsistent type",
+            ErrorKind::LengthInconsistency => "inconsistent length",
+            ErrorKind::TrailingBytes => "trailing bytes",
+            ErrorKind::NonCanonicalVarUint => "non canonical varuint",
+            ErrorKind::NestingTooDeep => "nesting too deep",
+        };
+        f.write_str(desc)
+    }
+}
+
+///Decoding error: `kind` tells what went wrong and `position` the offset in the input where it happened.
+///
+///Derived decoders add the path of the field. With the `error-type-names` feature, `type_name` gives the
+///innermost type being decoded, it is disabled by default because the names of all the decoded types
+///would be embedded in the contract.
+///
+///# Example
+///```
+/// # use ontio_std::abi::{ErrorKind, Source};
+///   let mut source = Source::new(&[1, 2]);
+///   let err = source.read::<u32>().unwrap_err();
+///   assert_eq!(err.kind(), ErrorKind::UnexpectedEOF);
+///   assert_eq!(err.position(), 0);
+///```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    kind: ErrorKind,
+    position: usize,
+    type_name: Option<&'static str>,
+    path: Vec<&'static str>, //innermost field first
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, position: usize) -> Self {
+        Error { kind, position, type_name: None, path: Vec::new() }
+    }
+
+    ///What went wrong.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    ///Offset in the input where the decoding failed.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    ///Name of the innermost type which failed to decode, only recorded with the `error-type-names` feature.
+    pub fn type_name(&self) -> Option<&'static str> {
+        self.type_name
+    }
+
+    ///Path of the field which failed to decode, such as `owner.balance`.
+    pub fn field_path(&self) -> String {
+        let path: Vec<&str> = self.path.iter().rev().cloned().collect();
+        path.join(".")
+    }
+
+    ///Prepend a field name to the path, used by derived decoders.
+    pub fn with_field(mut self, field: &'static str) -> Self {
+        self.path.push(field);
+        self
+    }
+
+    //record `T` as the type which failed to decode, unless an inner type is already recorded
+    #[allow(unused_mut)]
+    pub(crate) fn with_type_name<T>(mut self) -> Self {
+        #[cfg(feature = "error-type-names")]
+        {
+            if self.type_name.is_none() {
+                self.type_name = Some(core::any::type_name::<T>());
+            }
+        }
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} at byte {}", self.kind, self.position)?;
+        if let Some(name) = self.type_name {
+            write!(f, " while decoding {}", name)?;
+        }
+        if !self.path.is_empty() {
+            write!(f, " in field {}", self.field_path())?;
+        }
+        Ok(())
+    }
 }
 
 pub trait Encoder {
     fn encode(&self, sink: &mut Sink);
+
+    ///Number of bytes written by `encode` if it is known without encoding the value, used to size the
+    ///buffer up front. The default returns `None`, which leaves the `Sink` to grow as it is written.
+    fn encoded_len(&self) -> Option<usize> {
+        None
+    }
+
+    ///Encode the items of a slice one after another, `u8` overrides it to copy the bytes at once.
+    #[doc(hidden)]
+    fn encode_slice(items: &[Self], sink: &mut Sink)
+    where
+        Self: Sized,
+    {
+        for item in items {
+            item.encode(sink);
+        }
+    }
 }
 
 pub trait Dispatcher {
+    ///Decode the method name and arguments from `payload`, invoke the method and return the encoded result.
+    ///
+    ///The dispatchers generated by `#[contract]` abort the invocation with the message of the `DispatchError`
+    ///on an unknown method or an argument which can not be decoded, so the transaction fails. Only the
+    ///errors returned by methods of `Result<T, E>` are part of the output, encoded as `Result<T, DispatchError>`.
     fn dispatch(&mut self, payload: &[u8]) -> Vec<u8>;
+
+    ///Same as `dispatch`, but returns the failures of the dispatch itself as a `DispatchError`.
+    ///
+    ///The default implementation never fails, the dispatchers generated by `#[contract]` override it.
+    fn try_dispatch(&mut self, payload: &[u8]) -> Result<Vec<u8>, DispatchError> {
+        Ok(self.dispatch(payload))
+    }
 }
 
 #[doc(hidden)]
 pub trait Decoder<'a>: Sized {
     fn decode(source: &mut Source<'a>) -> Result<Self, Error>;
+
+    ///Decode `len` items into a vector, `u8` overrides it to copy the bytes at once.
+    #[doc(hidden)]
+    fn decode_vec(source: &mut Source<'a>, len: usize) -> Result<Vec<Self>, Error> {
+        let mut value = Vec::with_capacity(cmp::min(len, 1024));
+        for _i in 0..len {
+            value.push(source.read::<Self>()?);
+        }
+        Ok(value)
+    }
 }
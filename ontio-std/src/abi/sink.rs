@@ -26,6 +26,22 @@ impl Sink {
         Sink { buf: Vec::with_capacity(cap) }
     }
 
+    ///Create a sink holding the encoding of `val`. The buffer is allocated once with the exact size
+    ///when `val` knows its `encoded_len`, otherwise it grows as the value is written.
+    ///
+    ///# Example
+    ///```
+    /// # use ontio_std::abi::Sink;
+    /// let sink = Sink::with_value(&("transfer", 100u64));
+    /// assert_eq!(sink.bytes().len(), 9 + 8);
+    ///```
+    ///
+    pub fn with_value<T: Encoder>(val: &T) -> Self {
+        let mut sink = Sink::new(val.encoded_len().unwrap_or(16));
+        val.encode(&mut sink);
+        sink
+    }
+
     ///All data types that implement the encode interface can be serialized by calling the write method
     ///# Example
     ///```
@@ -90,7 +106,7 @@ impl Sink {
     pub(crate) fn write_varuint(&mut self, val: u64) {
         if val < 0xFD {
             self.write_byte(val as u8);
-        } else if val < 0xFFFF {
+        } else if val <= 0xFFFF {
             self.write_byte(0xFD);
             self.write_u16(val as u16);
         } else if val <= 0xFFFF_FFFF {
@@ -141,3 +157,30 @@ impl Sink {
         self.buf
     }
 }
+
+//compare the allocations of a guessed capacity with `with_value` when encoding a large payload
+#[test]
+fn bench_with_value() {
+    use crate::types::U128;
+    let payload: Vec<(Address, U128)> =
+        (0..1000u32).map(|i| (Address::repeat_byte(i as u8), U128::new(i as u128))).collect();
+    let len = payload.encoded_len().unwrap();
+
+    let mut sink = Sink::new(16);
+    let mut reallocs = 0;
+    sink.write_varuint(payload.len() as u64);
+    for item in payload.iter() {
+        let cap = sink.buf.capacity();
+        sink.write(item);
+        if sink.buf.capacity() != cap {
+            reallocs += 1;
+        }
+    }
+    assert_eq!(sink.bytes().len(), len);
+    assert!(reallocs > 5);
+    assert!(sink.buf.capacity() > len);
+
+    let sink = Sink::with_value(&payload);
+    assert_eq!(sink.bytes().len(), len);
+    assert_eq!(sink.buf.capacity(), len);
+}
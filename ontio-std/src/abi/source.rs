@@ -1,5 +1,5 @@
 use super::Decoder;
-use super::Error;
+use super::{Error, ErrorKind};
 use byteorder::{ByteOrder, LittleEndian};
 
 use crate::prelude::U128;
@@ -21,6 +21,7 @@ pub(crate) fn varuint_encode_size(val: u64) -> usize {
 pub struct Source<'a> {
     buf: &'a [u8],
     pos: usize,
+    strict: bool,
 }
 
 impl<'a> Source<'a> {
@@ -36,12 +37,65 @@ impl<'a> Source<'a> {
     /// ```
     ///
     pub fn new(data: &'a [u8]) -> Self {
-        Self { buf: data, pos: 0 }
+        Self { buf: data, pos: 0, strict: false }
+    }
+
+    ///Create a source in strict mode: lengths larger than the remaining data are rejected as soon as
+    ///they are read, and `finish` fails if the data is not fully consumed.
+    /// # Example
+    /// ```
+    /// # use ontio_std::abi::{Source, Sink};
+    ///   let mut sink = Sink::new(0);
+    ///   sink.write(1u32);
+    ///   sink.write(2u32);
+    ///   let mut source = Source::strict(sink.bytes());
+    ///   let res: u32 = source.read().unwrap();
+    ///   assert_eq!(res, 1);
+    ///   assert!(source.finish().is_err());
+    /// ```
+    ///
+    pub fn strict(data: &'a [u8]) -> Self {
+        Self { buf: data, pos: 0, strict: true }
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    ///Check that all the data has been read in strict mode, always succeed otherwise.
+    pub fn finish(&self) -> Result<(), Error> {
+        if self.strict && !self.is_empty() {
+            Err(self.error(ErrorKind::TrailingBytes))
+        } else {
+            Ok(())
+        }
+    }
+
+    ///Number of bytes not read yet.
+    /// # Example
+    /// ```
+    /// # use ontio_std::abi::{Source, Sink};
+    ///   let mut sink = Sink::new(0);
+    ///   sink.write(1u32);
+    ///   sink.write(true);
+    ///   let mut source = Source::new(sink.bytes());
+    ///   let _: u32 = source.read().unwrap();
+    ///   assert_eq!(source.remaining(), 1);
+    ///   assert!(!source.is_empty());
+    /// ```
+    ///
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    ///Whether all the data has been read.
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
     }
 
     pub(crate) fn next_bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
         if self.buf.len() - self.pos < len {
-            Err(Error::UnexpectedEOF)
+            Err(self.error(ErrorKind::UnexpectedEOF))
         } else {
             let bytes = &self.buf[self.pos..self.pos + len];
             self.pos += len;
@@ -60,8 +114,17 @@ impl<'a> Source<'a> {
     /// ```
     ///
     pub fn read_bytes(&mut self) -> Result<&'a [u8], Error> {
-        let n = self.read_varuint()?;
-        self.next_bytes(n as usize)
+        let n = self.read_length()?;
+        self.next_bytes(n)
+    }
+
+    //read the varuint length of a collection, every item takes at least one byte
+    pub(crate) fn read_length(&mut self) -> Result<usize, Error> {
+        let len = self.read_varuint()?;
+        if self.strict && len > self.remaining() as u64 {
+            return Err(self.error(ErrorKind::LengthInconsistency));
+        }
+        Ok(len as usize)
     }
 
     ///Parse the bytearray data into the original data type. The original data type must implement the decoder interface.
@@ -80,7 +143,12 @@ impl<'a> Source<'a> {
     /// ```
     ///
     pub fn read<T: Decoder<'a>>(&mut self) -> Result<T, Error> {
-        T::decode(self)
+        T::decode(self).map_err(Error::with_type_name::<T>)
+    }
+
+    ///Create an error of `kind` at the current position.
+    pub fn error(&self, kind: ErrorKind) -> Error {
+        Error::new(kind, self.pos)
     }
 
     pub fn read_address(&mut self) -> Result<&'a Address, Error> {
@@ -89,15 +157,18 @@ impl<'a> Source<'a> {
     }
     pub fn read_native_address(&mut self) -> Result<&'a Address, Error> {
         let l = self.read_byte()?;
-        assert_eq!(l, 20);
+        if l != 20 {
+            return Err(self.error(ErrorKind::LengthInconsistency));
+        }
         self.read_address()
     }
 
     pub fn read_native_varuint(&mut self) -> Result<u64, Error> {
         let l = self.read_byte()?;
         let val = self.read_varuint()?;
-        let l_new = varuint_encode_size(val);
-        assert_eq!(l as usize, l_new);
+        if l as usize != varuint_encode_size(val) {
+            return Err(self.error(ErrorKind::LengthInconsistency));
+        }
         Ok(val)
     }
 
@@ -124,7 +195,7 @@ impl<'a> Source<'a> {
     ///
     pub fn read_byte(&mut self) -> Result<u8, Error> {
         if self.pos >= self.buf.len() {
-            Err(Error::UnexpectedEOF)
+            Err(self.error(ErrorKind::UnexpectedEOF))
         } else {
             let b = self.buf[self.pos];
             self.pos += 1;
@@ -147,7 +218,7 @@ impl<'a> Source<'a> {
         match self.read_byte()? {
             0 => Ok(false),
             1 => Ok(true),
-            _ => Err(Error::IrregularData),
+            _ => Err(self.error(ErrorKind::IrregularData)),
         }
     }
 
@@ -169,7 +240,7 @@ impl<'a> Source<'a> {
     #[allow(unused)]
     pub fn skip(&mut self, n: usize) -> Result<(), Error> {
         if self.buf.len() - self.pos < n {
-            Err(Error::UnexpectedEOF)
+            Err(self.error(ErrorKind::UnexpectedEOF))
         } else {
             self.pos += n;
             Ok(())
@@ -277,7 +348,7 @@ impl<'a> Source<'a> {
             if len == varuint_encode_size(val) {
                 Ok(val)
             } else {
-                Err(Error::IrregularData)
+                Err(self.error(ErrorKind::NonCanonicalVarUint))
             }
         })
     }
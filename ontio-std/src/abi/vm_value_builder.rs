@@ -1,13 +1,16 @@
 use super::event_builder::{
     TYPE_ADDRESS, TYPE_BOOL, TYPE_BYTEARRAY, TYPE_H256, TYPE_INT, TYPE_STRING,
 };
-use super::Error;
 use super::Source;
-use super::{VmValueBuilderCommon, VmValueDecoder, VmValueEncoder};
+use super::{Error, ErrorKind};
+use super::{VmValue, VmValueBuilderCommon, VmValueDecoder, VmValueEncoder};
 use crate::abi::event_builder::TYPE_LIST;
 use crate::prelude::*;
 use core::ops::{Deref, DerefMut};
 
+///Deepest list nesting accepted by `VmValueParser::read_value`.
+pub const MAX_VALUE_DEPTH: usize = 32;
+
 pub struct VmValueBuilder {
     pub(crate) common: VmValueBuilderCommon,
 }
@@ -88,10 +91,36 @@ impl VmValueBuilder {
         self.common.bool(b);
     }
 
+    //write a list of `len` values as a single entry, `f` must write exactly `len` values
+    pub(crate) fn list_of<F: FnOnce(&mut VmValueBuilder)>(&mut self, len: u32, f: F) {
+        self.common.sink.write_byte(TYPE_LIST);
+        self.common.sink.write_u32(len);
+        let num_entry = self.common.num_entry;
+        f(self);
+        self.common.num_entry = num_entry + 1;
+    }
+
     pub fn h256(&mut self, hash: &H256) {
         self.common.h256(hash);
     }
 
+    ///Write a value of any type, nested lists are written recursively.
+    pub fn value(&mut self, value: &VmValue) {
+        match value {
+            VmValue::ByteArray(bytes) => self.bytearray(bytes),
+            VmValue::String(s) => self.string(s),
+            VmValue::Address(addr) => self.address(addr),
+            VmValue::Bool(b) => self.bool(*b),
+            VmValue::Int(num) => self.number(*num),
+            VmValue::H256(hash) => self.h256(hash),
+            VmValue::List(list) => self.list_of(list.len() as u32, |builder| {
+                for value in list {
+                    builder.value(value);
+                }
+            }),
+        }
+    }
+
     pub fn bytes(self) -> Vec<u8> {
         let num_entry = self.common.num_entry;
         let mut buf = self.common.sink.into();
@@ -118,17 +147,17 @@ impl<'a> VmValueParser<'a> {
     pub fn string(&mut self) -> Result<&'a str, Error> {
         let ty = self.source.read_byte()?;
         if ty != TYPE_STRING {
-            return Err(Error::TypeInconsistency);
+            return Err(self.source.error(ErrorKind::TypeInconsistency));
         }
         let l = self.source.read_u32()?;
         let buf = self.source.next_bytes(l as usize)?;
-        str::from_utf8(buf).map_err(|_| Error::InvalidUtf8)
+        str::from_utf8(buf).map_err(|_| self.source.error(ErrorKind::InvalidUtf8))
     }
 
     pub fn bytearray(&mut self) -> Result<&'a [u8], Error> {
         let ty = self.source.read_byte()?;
         if ty != TYPE_BYTEARRAY || ty == TYPE_STRING {
-            return Err(Error::TypeInconsistency);
+            return Err(self.source.error(ErrorKind::TypeInconsistency));
         }
         let l = self.source.read_u32()?;
         self.source.next_bytes(l as usize)
@@ -137,7 +166,7 @@ impl<'a> VmValueParser<'a> {
     pub fn address(&mut self) -> Result<&'a Address, Error> {
         let ty = self.source.read_byte()?;
         if ty != TYPE_ADDRESS {
-            return Err(Error::TypeInconsistency);
+            return Err(self.source.error(ErrorKind::TypeInconsistency));
         }
         self.source.read_address()
     }
@@ -145,7 +174,7 @@ impl<'a> VmValueParser<'a> {
     pub fn number(&mut self) -> Result<U128, Error> {
         let ty = self.source.read_byte()?;
         if ty != TYPE_INT {
-            return Err(Error::TypeInconsistency);
+            return Err(self.source.error(ErrorKind::TypeInconsistency));
         }
         self.source.read_u128()
     }
@@ -155,24 +184,80 @@ impl<'a> VmValueParser<'a> {
         match ty {
             TYPE_BOOL => self.source.read_bool(),
             TYPE_INT => Ok(self.source.read_u128()?.raw() != 0),
-            _ => Err(Error::TypeInconsistency),
+            _ => Err(self.source.error(ErrorKind::TypeInconsistency)),
         }
     }
 
+    ///Read the header of a nested list and return its number of entries, the entries follow it.
+    pub fn list(&mut self) -> Result<u32, Error> {
+        let ty = self.source.read_byte()?;
+        if ty != TYPE_LIST {
+            return Err(self.source.error(ErrorKind::TypeInconsistency));
+        }
+        self.source.read_u32()
+    }
+
     pub fn h256(&mut self) -> Result<&'a H256, Error> {
         let ty = self.source.read_byte()?;
         if ty != TYPE_H256 {
-            return Err(Error::TypeInconsistency);
+            return Err(self.source.error(ErrorKind::TypeInconsistency));
         }
         self.source.read_h256()
     }
+
+    ///Read the next value whatever its type, nested lists are read recursively.
+    ///
+    ///Lists nested more than `MAX_VALUE_DEPTH` levels deep are rejected with `ErrorKind::NestingTooDeep`,
+    ///so that a hostile payload can not exhaust the stack.
+    pub fn read_value(&mut self) -> Result<VmValue, Error> {
+        self.read_value_at(0)
+    }
+
+    fn read_value_at(&mut self, depth: usize) -> Result<VmValue, Error> {
+        let ty = self.source.read_byte()?;
+        self.source.backup(1);
+        let value = match ty {
+            TYPE_BYTEARRAY => VmValue::ByteArray(self.bytearray()?.to_vec()),
+            TYPE_STRING => VmValue::String(self.string()?.to_string()),
+            TYPE_ADDRESS => VmValue::Address(self.address()?.clone()),
+            TYPE_BOOL => VmValue::Bool(self.bool()?),
+            TYPE_INT => VmValue::Int(self.number()?),
+            TYPE_H256 => VmValue::H256(self.h256()?.clone()),
+            TYPE_LIST => {
+                if depth >= MAX_VALUE_DEPTH {
+                    return Err(self.source.error(ErrorKind::NestingTooDeep));
+                }
+                let len = self.list()?;
+                let mut list = Vec::with_capacity(cmp::min(len, 1024) as usize);
+                for _i in 0..len {
+                    list.push(self.read_value_at(depth + 1)?);
+                }
+                VmValue::List(list)
+            }
+            _ => return Err(self.source.error(ErrorKind::IrregularData)),
+        };
+        Ok(value)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate as ontio_std;
     use crate::abi::Sink;
 
+    #[derive(VmValueEncoder, VmValueDecoder, Debug, PartialEq)]
+    struct BalanceOf {
+        owner: Address,
+    }
+
+    #[derive(VmValueEncoder, VmValueDecoder, Debug, PartialEq)]
+    enum Action {
+        Pause,
+        Mint(Address, U128),
+        Transfer { from: Address, to: Address, amount: U128 },
+    }
+
     #[test]
     fn test_builder() {
         let mut builder = VmValueBuilder::new();
@@ -195,6 +280,153 @@ mod tests {
         sink.write_u32(1);
         sink.write_byte(TYPE_ADDRESS); // first param
         sink.write(&addr);
-        assert_eq!(builder.bytes(), sink.into());
+        let expected = sink.into();
+        assert_eq!(builder.bytes(), expected);
+
+        let mut builder = VmValueBuilder::new();
+        builder.write("balanceOf");
+        builder.write(vec![addr]);
+        assert_eq!(builder.bytes(), expected);
+
+        let mut builder = VmValueBuilder::new();
+        builder.write("balanceOf");
+        builder.write(BalanceOf { owner: addr });
+        assert_eq!(builder.bytes(), expected);
+        let mut parser = VmValueParser::new(&expected);
+        assert_eq!(parser.read(), Ok(("balanceOf", BalanceOf { owner: addr })));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let addrs = vec![Address::repeat_byte(1), Address::repeat_byte(2)];
+        let nested = vec![vec![U128::new(1)], vec![], vec![U128::new(2), U128::new(3)]];
+        let mut builder = VmValueBuilder::new();
+        builder.write(&addrs);
+        builder.write("owned".to_string());
+        builder.write(vec![1u8, 2, 3]);
+        builder.write(I128::new(-100));
+        builder.write(Some(U128::new(5)));
+        builder.write(None::<bool>);
+        builder.write(&nested);
+        builder.write((true, H256::repeat_byte(3)));
+        builder.write(&addrs[..]);
+        let buf = builder.bytes();
+
+        let mut parser = VmValueParser::new(&buf);
+        #[allow(clippy::type_complexity)]
+        let res: (
+            Vec<Address>,
+            String,
+            Vec<u8>,
+            I128,
+            Option<U128>,
+            Option<bool>,
+            Vec<Vec<U128>>,
+            (bool, H256),
+            Vec<&Address>,
+        ) = parser.read().unwrap();
+        assert_eq!(res.0, addrs);
+        assert_eq!(res.1, "owned");
+        assert_eq!(res.2, vec![1, 2, 3]);
+        assert_eq!(res.3.raw(), -100);
+        assert_eq!(res.4, Some(U128::new(5)));
+        assert_eq!(res.5, None);
+        assert_eq!(res.6, nested);
+        assert_eq!(res.7, (true, H256::repeat_byte(3)));
+        assert_eq!(res.8, vec![&addrs[0], &addrs[1]]);
+        assert!(parser.source.is_empty());
+    }
+
+    #[test]
+    fn test_read_value() {
+        let addr = Address::repeat_byte(1);
+        let mut builder = VmValueBuilder::new();
+        builder.write(("transfer", (&addr, vec![U128::new(1), U128::new(2)]), true));
+        builder.write(vec![1u8, 2]);
+        builder.write(H256::repeat_byte(2));
+        let buf = builder.bytes();
+
+        let mut parser = VmValueParser::new(&buf);
+        assert_eq!(parser.list(), Ok(3));
+        let value = parser.read_value().unwrap();
+        let expected = VmValue::List(vec![
+            VmValue::String("transfer".to_string()),
+            VmValue::List(vec![
+                VmValue::Address(addr),
+                VmValue::List(vec![VmValue::Int(U128::new(1)), VmValue::Int(U128::new(2))]),
+            ]),
+            VmValue::Bool(true),
+        ]);
+        assert_eq!(value, expected);
+        assert_eq!(parser.read_value(), Ok(VmValue::ByteArray(vec![1, 2])));
+        assert_eq!(parser.read(), Ok(VmValue::H256(H256::repeat_byte(2))));
+        assert!(parser.source.is_empty());
+
+        let mut builder = VmValueBuilder::new();
+        builder.value(&expected);
+        builder.write(VmValue::ByteArray(vec![1, 2]));
+        builder.value(&VmValue::H256(H256::repeat_byte(2)));
+        assert_eq!(builder.bytes(), buf);
+
+        let mut value = VmValue::Bool(true);
+        for _ in 0..MAX_VALUE_DEPTH {
+            value = VmValue::List(vec![value]);
+        }
+        let mut builder = VmValueBuilder::new();
+        builder.value(&value);
+        let buf = builder.bytes();
+        let mut parser = VmValueParser::new(&buf);
+        parser.list().unwrap();
+        assert_eq!(parser.read_value(), Ok(value.clone()));
+
+        let mut builder = VmValueBuilder::new();
+        builder.value(&VmValue::List(vec![value]));
+        let buf = builder.bytes();
+        let mut parser = VmValueParser::new(&buf);
+        parser.list().unwrap();
+        assert_eq!(parser.read_value().unwrap_err().kind(), ErrorKind::NestingTooDeep);
+    }
+
+    #[test]
+    fn test_derive_enum() {
+        let from = Address::repeat_byte(1);
+        let to = Address::repeat_byte(2);
+        let actions = vec![
+            Action::Pause,
+            Action::Mint(to, U128::new(10)),
+            Action::Transfer { from, to, amount: U128::new(5) },
+        ];
+        let mut builder = VmValueBuilder::new();
+        builder.write(&actions);
+        let buf = builder.bytes();
+
+        let mut builder = VmValueBuilder::new();
+        builder.list_of(3, |builder| {
+            builder.write(("Pause",));
+            builder.write(("Mint", to, U128::new(10)));
+            builder.write(("Transfer", from, to, U128::new(5)));
+        });
+        assert_eq!(builder.bytes(), buf);
+
+        let mut parser = VmValueParser::new(&buf);
+        assert_eq!(parser.list(), Ok(1));
+        assert_eq!(parser.read::<Vec<Action>>(), Ok(actions));
+
+        let mut builder = VmValueBuilder::new();
+        builder.write(("Transfer", from, to, true));
+        builder.write(("Burn", U128::new(1)));
+        builder.write(("Mint", to));
+        let buf = builder.bytes();
+        let mut parser = VmValueParser::new(&buf);
+        assert_eq!(parser.list(), Ok(3));
+        let err = parser.read::<Action>().err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::TypeInconsistency);
+        assert_eq!(err.field_path(), "Transfer.amount");
+        let mut parser = VmValueParser::new(&buf);
+        parser.list().unwrap();
+        parser.read_value().unwrap();
+        assert_eq!(parser.read::<Action>().err().unwrap().kind(), ErrorKind::IrregularData);
+        parser.read_value().unwrap(); //the remaining field of the unknown variant
+        assert_eq!(parser.read::<Action>().err().unwrap().kind(), ErrorKind::LengthInconsistency);
     }
 }
@@ -1,7 +1,29 @@
-use super::Error;
+use super::{Error, ErrorKind};
 use crate::abi::{VmValueBuilder, VmValueParser};
 use crate::prelude::*;
 
+///NeoVM value of any type, used to inspect or forward results whose shape is not known in advance.
+///
+///# Example
+///```no_run
+/// # use ontio_std::abi::{VmValue, VmValueParser};
+/// # let result: Vec<u8> = vec![];
+///   let mut parser = VmValueParser::new(&result);
+///   if let Ok(VmValue::List(items)) = parser.read_value() {
+///       assert!(items.iter().all(|item| matches!(item, VmValue::Int(_))));
+///   }
+///```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VmValue {
+    ByteArray(Vec<u8>),
+    String(String),
+    Address(Address),
+    Bool(bool),
+    Int(U128),
+    H256(H256),
+    List(Vec<VmValue>),
+}
+
 pub trait VmValueEncoder {
     fn serialize(&self, sink: &mut VmValueBuilder);
 }
@@ -42,12 +64,64 @@ impl VmValueEncoder for Address {
     }
 }
 
+impl VmValueEncoder for String {
+    fn serialize(&self, builder: &mut VmValueBuilder) {
+        builder.string(self);
+    }
+}
+
+impl VmValueEncoder for Vec<u8> {
+    fn serialize(&self, builder: &mut VmValueBuilder) {
+        builder.bytearray(self);
+    }
+}
+
+//NeoVM integers are signed, negative values are written in two's complement
+impl VmValueEncoder for I128 {
+    fn serialize(&self, builder: &mut VmValueBuilder) {
+        builder.number(self.to_u128());
+    }
+}
+
+impl<T: VmValueEncoder> VmValueEncoder for &[T] {
+    fn serialize(&self, builder: &mut VmValueBuilder) {
+        builder.list_of(self.len() as u32, |builder| {
+            for item in self.iter() {
+                builder.write(item);
+            }
+        });
+    }
+}
+
+impl<T: VmValueEncoder> VmValueEncoder for Vec<T> {
+    fn serialize(&self, builder: &mut VmValueBuilder) {
+        self.as_slice().serialize(builder)
+    }
+}
+
+///`None` is written as an empty list and `Some` as a list holding the value.
+impl<T: VmValueEncoder> VmValueEncoder for Option<T> {
+    fn serialize(&self, builder: &mut VmValueBuilder) {
+        builder.list_of(self.is_some() as u32, |builder| {
+            if let Some(val) = self {
+                builder.write(val);
+            }
+        });
+    }
+}
+
 impl<T: VmValueEncoder> VmValueEncoder for &T {
     fn serialize(&self, builder: &mut VmValueBuilder) {
         (*self).serialize(builder)
     }
 }
 
+impl VmValueEncoder for VmValue {
+    fn serialize(&self, builder: &mut VmValueBuilder) {
+        builder.value(self);
+    }
+}
+
 pub trait VmValueDecoder<'a>: Sized {
     fn deserialize(parser: &mut VmValueParser<'a>) -> Result<Self, Error>;
 }
@@ -88,8 +162,59 @@ impl<'a> VmValueDecoder<'a> for &'a Address {
     }
 }
 
-impl<'a, T: VmValueDecoder<'a>> VmValueDecoder<'a> for &'a T {
-    fn deserialize(parser: &mut VmValueParser<'a>) -> Result<&'a T, Error> {
-        parser.read()
+impl<'a> VmValueDecoder<'a> for String {
+    fn deserialize(parser: &mut VmValueParser<'a>) -> Result<Self, Error> {
+        parser.string().map(ToString::to_string)
+    }
+}
+
+impl<'a> VmValueDecoder<'a> for Vec<u8> {
+    fn deserialize(parser: &mut VmValueParser<'a>) -> Result<Self, Error> {
+        parser.bytearray().map(<[u8]>::to_vec)
+    }
+}
+
+impl<'a> VmValueDecoder<'a> for Address {
+    fn deserialize(parser: &mut VmValueParser<'a>) -> Result<Self, Error> {
+        parser.address().map(Clone::clone)
+    }
+}
+
+impl<'a> VmValueDecoder<'a> for H256 {
+    fn deserialize(parser: &mut VmValueParser<'a>) -> Result<Self, Error> {
+        parser.h256().map(Clone::clone)
+    }
+}
+
+impl<'a> VmValueDecoder<'a> for I128 {
+    fn deserialize(parser: &mut VmValueParser<'a>) -> Result<Self, Error> {
+        parser.number().map(U128::to_i128)
+    }
+}
+
+impl<'a, T: VmValueDecoder<'a>> VmValueDecoder<'a> for Vec<T> {
+    fn deserialize(parser: &mut VmValueParser<'a>) -> Result<Self, Error> {
+        let len = parser.list()?;
+        let mut list = Vec::with_capacity(cmp::min(len, 1024) as usize);
+        for _i in 0..len {
+            list.push(parser.read()?);
+        }
+        Ok(list)
+    }
+}
+
+impl<'a, T: VmValueDecoder<'a>> VmValueDecoder<'a> for Option<T> {
+    fn deserialize(parser: &mut VmValueParser<'a>) -> Result<Self, Error> {
+        match parser.list()? {
+            0 => Ok(None),
+            1 => Ok(Some(parser.read()?)),
+            _ => Err(parser.source.error(ErrorKind::LengthInconsistency)),
+        }
+    }
+}
+
+impl<'a> VmValueDecoder<'a> for VmValue {
+    fn deserialize(parser: &mut VmValueParser<'a>) -> Result<Self, Error> {
+        parser.read_value()
     }
 }
@@ -3,6 +3,8 @@ use crate::prelude::*;
 pub mod neo {
     use crate::prelude::*;
 
+    ///Call a NeoVM contract with `param` as the single entry of the parameter list, a tuple is written
+    ///as a nested list of its items.
     pub fn call_contract<T: crate::abi::VmValueEncoder>(
         contract_address: &Address, param: T,
     ) -> Vec<u8> {
@@ -10,6 +12,25 @@ pub mod neo {
         param.serialize(&mut builder);
         crate::runtime::call_contract(contract_address, &builder.bytes())
     }
+
+    ///Call the `method` of a NeoVM contract, the parameter list holding the method name followed by
+    ///the list of `args`, which is the layout expected by contracts dispatching on the method name.
+    ///# Example
+    ///```no_run
+    /// # use ontio_std::contract::neo;
+    /// # use ontio_std::prelude::*;
+    /// # use ontio_std::types::U128;
+    /// # let (token, from, to) = (Address::zero(), Address::zero(), Address::zero());
+    ///   neo::call_method(&token, "transfer", (&from, &to, U128::new(100)));
+    ///```
+    pub fn call_method<T: crate::abi::VmValueEncoder>(
+        contract_address: &Address, method: &str, args: T,
+    ) -> Vec<u8> {
+        let mut builder = crate::abi::VmValueBuilder::new();
+        builder.write(method);
+        builder.write(args);
+        crate::runtime::call_contract(contract_address, &builder.bytes())
+    }
 }
 
 pub mod eth {
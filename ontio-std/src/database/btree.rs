@@ -0,0 +1,658 @@
+use super::list::gen_key;
+use crate::abi::{Decoder, Encoder, Error, ErrorKind, Sink, Source};
+use crate::database;
+use crate::prelude::*;
+use alloc::collections::BTreeMap;
+use core::ops::{Bound, RangeBounds};
+
+//max entries of a leaf node and max children of an internal node
+const NODE_SIZE: usize = 64;
+//node with less entries or children borrows from a sibling, or is merged with it
+const MIN_SIZE: usize = NODE_SIZE / 4;
+
+enum Node<K, V> {
+    Leaf { entries: Vec<(K, V)>, next: Option<u32> },
+    //child `i` holds the keys in `keys[i - 1]..keys[i]`
+    Internal { keys: Vec<K>, children: Vec<u32> },
+}
+
+impl<K, V> Node<K, V> {
+    fn size(&self) -> usize {
+        match self {
+            Node::Leaf { entries, .. } => entries.len(),
+            Node::Internal { children, .. } => children.len(),
+        }
+    }
+
+    fn entries(&mut self) -> &mut Vec<(K, V)> {
+        match self {
+            Node::Leaf { entries, .. } => entries,
+            Node::Internal { .. } => panic!("btree leaf node expected"),
+        }
+    }
+
+    fn internal(&mut self) -> (&mut Vec<K>, &mut Vec<u32>) {
+        match self {
+            Node::Internal { keys, children } => (keys, children),
+            Node::Leaf { .. } => panic!("btree internal node expected"),
+        }
+    }
+}
+
+impl<K: Encoder, V: Encoder> Encoder for Node<K, V> {
+    fn encode(&self, sink: &mut Sink) {
+        match self {
+            Node::Leaf { entries, next } => {
+                sink.write_byte(0);
+                sink.write(next);
+                sink.write(entries);
+            }
+            Node::Internal { keys, children } => {
+                sink.write_byte(1);
+                sink.write(keys);
+                sink.write(children);
+            }
+        }
+    }
+
+    fn encoded_len(&self) -> Option<usize> {
+        match self {
+            Node::Leaf { entries, next } => Some(1 + next.encoded_len()? + entries.encoded_len()?),
+            Node::Internal { keys, children } => {
+                Some(1 + keys.encoded_len()? + children.encoded_len()?)
+            }
+        }
+    }
+}
+
+impl<'a, K: Decoder<'a>, V: Decoder<'a>> Decoder<'a> for Node<K, V> {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        match source.read_byte()? {
+            0 => {
+                let next = source.read()?;
+                let entries = source.read()?;
+                Ok(Node::Leaf { entries, next })
+            }
+            1 => {
+                let keys = source.read()?;
+                let children = source.read()?;
+                Ok(Node::Internal { keys, children })
+            }
+            _ => Err(source.error(ErrorKind::IrregularData)),
+        }
+    }
+}
+
+///Persistent ordered map, stored as a B+ tree.
+///
+///Entries are kept sorted in leaf nodes of at most 64 entries, linked to the next leaf for iteration,
+///and internal nodes of at most 64 children route a key to its leaf. Every node is stored under its own
+///key derived from the root key, which stores a fixed size header: the number of entries, the id of the
+///root node and the height of the tree.
+///
+///Nodes are loaded on demand and cached, modified nodes are written back by `flush`, which is called
+///automatically when the store is dropped. An insert or a remove only modifies the nodes on the path
+///from the root to the leaf of the key, along with a sibling when a node is split or merged, so its
+///storage cost grows with the logarithm of the number of entries.
+///
+///# Example
+///```no_run
+/// # use ontio_std::database::BTreeStore;
+///   let mut scores: BTreeStore<u64, String> = BTreeStore::open(b"scores");
+///   scores.insert(10, "alice".to_string());
+///   scores.insert(20, "bob".to_string());
+///   assert_eq!(scores.lower_bound(&15).map(|(k, _)| *k), Some(20));
+///   for (score, name) in scores.range(..=10) {
+///       //...
+///   }
+///```
+pub struct BTreeStore<K: Encoder, V: Encoder> {
+    key: Vec<u8>,
+    len: u32,
+    next_node_id: u32,
+    root: Option<u32>,
+    height: u32, //levels of internal nodes above the leaves
+    cache: BTreeMap<u32, Node<K, V>>,
+    need_flush: Vec<u32>,
+    need_delete: Vec<u32>,
+    header_dirty: bool,
+}
+
+impl<K: Encoder, V: Encoder> Drop for BTreeStore<K, V> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl<K, V> BTreeStore<K, V>
+where
+    for<'a> K: Decoder<'a> + Encoder + Ord + Clone + 'static,
+    for<'a> V: Decoder<'a> + Encoder + 'static,
+{
+    pub fn open(key: &[u8]) -> Self {
+        let (len, next_node_id, root, height) = database::get(key).unwrap_or_default();
+        BTreeStore {
+            key: key.to_vec(),
+            len,
+            next_node_id,
+            root,
+            height,
+            cache: BTreeMap::new(),
+            need_flush: Vec::new(),
+            need_delete: Vec::new(),
+            header_dirty: false,
+        }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn load(&mut self, id: u32) -> &mut Node<K, V> {
+        let key = &self.key;
+        self.cache.entry(id).or_insert_with(|| database::get(gen_key(key, id)).unwrap())
+    }
+
+    fn mark_dirty(&mut self, id: u32) {
+        if !self.need_flush.contains(&id) {
+            self.need_flush.push(id);
+        }
+    }
+
+    //take a node out of the cache to modify it along with other nodes, `put` stores it back
+    fn take(&mut self, id: u32) -> Node<K, V> {
+        self.load(id);
+        self.cache.remove(&id).unwrap()
+    }
+
+    fn put(&mut self, id: u32, node: Node<K, V>) {
+        self.cache.insert(id, node);
+        self.mark_dirty(id);
+    }
+
+    fn alloc(&mut self, node: Node<K, V>) -> u32 {
+        let id = self.next_node_id;
+        self.next_node_id += 1;
+        self.header_dirty = true;
+        self.put(id, node);
+        id
+    }
+
+    fn free(&mut self, id: u32) {
+        self.cache.remove(&id);
+        self.need_flush.retain(|&v| v != id);
+        self.need_delete.push(id);
+    }
+
+    //the leaf which may contain `key`, with the internal nodes from the root to it and the index of
+    //the child followed in each
+    fn find_leaf(&mut self, key: &K) -> Option<(Vec<(u32, usize)>, u32)> {
+        let mut id = self.root?;
+        let mut path = Vec::with_capacity(self.height as usize);
+        for _ in 0..self.height {
+            let (keys, children) = self.load(id).internal();
+            let idx = match keys.binary_search(key) {
+                Ok(i) => i + 1,
+                Err(i) => i,
+            };
+            path.push((id, idx));
+            id = children[idx];
+        }
+        Some((path, id))
+    }
+
+    //the first or the last leaf
+    fn edge_leaf(&mut self, last: bool) -> Option<u32> {
+        let mut id = self.root?;
+        for _ in 0..self.height {
+            let (_, children) = self.load(id).internal();
+            id = if last { children[children.len() - 1] } else { children[0] };
+        }
+        Some(id)
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let (_, id) = self.find_leaf(key)?;
+        let entries = self.load(id).entries();
+        match entries.binary_search_by(|(k, _)| k.cmp(key)) {
+            Ok(pos) => Some(&entries[pos].1),
+            Err(_) => None,
+        }
+    }
+
+    pub fn contains_key(&mut self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    ///Insert a key-value pair, return the old value if the key was present.
+    pub fn insert(&mut self, key: K, val: V) -> Option<V> {
+        let (path, id) = match self.find_leaf(&key) {
+            Some(found) => found,
+            None => {
+                let id = self.alloc(Node::Leaf { entries: vec![(key, val)], next: None });
+                self.root = Some(id);
+                self.len = 1;
+                return None;
+            }
+        };
+        self.mark_dirty(id);
+        let entries = self.load(id).entries();
+        match entries.binary_search_by(|(k, _)| k.cmp(&key)) {
+            Ok(pos) => return Some(core::mem::replace(&mut entries[pos].1, val)),
+            Err(pos) => entries.insert(pos, (key, val)),
+        }
+        self.len += 1;
+        self.header_dirty = true;
+        if self.load(id).size() > NODE_SIZE {
+            self.split(path, id);
+        }
+        None
+    }
+
+    //split the overfull node `id` in two and add the right half to its parent, up to the root
+    fn split(&mut self, mut path: Vec<(u32, usize)>, mut id: u32) {
+        loop {
+            let (sep, right) = match self.load(id) {
+                Node::Leaf { entries, next } => {
+                    let right = entries.split_off(entries.len() / 2);
+                    (right[0].0.clone(), Node::Leaf { entries: right, next: next.take() })
+                }
+                Node::Internal { keys, children } => {
+                    let right_keys = keys.split_off(keys.len() / 2 + 1);
+                    let sep = keys.pop().unwrap();
+                    let right_children = children.split_off(keys.len() + 1);
+                    (sep, Node::Internal { keys: right_keys, children: right_children })
+                }
+            };
+            let right_id = self.alloc(right);
+            if let Node::Leaf { next, .. } = self.load(id) {
+                *next = Some(right_id);
+            }
+            self.mark_dirty(id);
+            match path.pop() {
+                Some((parent, idx)) => {
+                    let (keys, children) = self.load(parent).internal();
+                    keys.insert(idx, sep);
+                    children.insert(idx + 1, right_id);
+                    self.mark_dirty(parent);
+                    if self.load(parent).size() <= NODE_SIZE {
+                        return;
+                    }
+                    id = parent;
+                }
+                None => {
+                    let root = Node::Internal { keys: vec![sep], children: vec![id, right_id] };
+                    self.root = Some(self.alloc(root));
+                    self.height += 1;
+                    return;
+                }
+            }
+        }
+    }
+
+    ///Remove a key, return its value if the key was present.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (path, id) = self.find_leaf(key)?;
+        let entries = self.load(id).entries();
+        let (_, val) = match entries.binary_search_by(|(k, _)| k.cmp(key)) {
+            Ok(pos) => entries.remove(pos),
+            Err(_) => return None,
+        };
+        self.mark_dirty(id);
+        self.len -= 1;
+        self.header_dirty = true;
+        self.rebalance(path, id);
+        Some(val)
+    }
+
+    //bring node `id` back to the minimum size by borrowing from or merging with its left sibling,
+    //or its right sibling for the first child, a merge removes a child of the parent which is
+    //rebalanced in turn
+    fn rebalance(&mut self, mut path: Vec<(u32, usize)>, mut id: u32) {
+        loop {
+            let (parent, idx) = match path.pop() {
+                Some(last) => last,
+                None => {
+                    //the root has no minimum size, it is dropped once empty or left with one child
+                    match self.load(id) {
+                        Node::Leaf { entries, .. } if entries.is_empty() => {
+                            self.free(id);
+                            self.root = None;
+                        }
+                        Node::Internal { children, .. } if children.len() == 1 => {
+                            self.root = Some(children[0]);
+                            self.free(id);
+                            self.height -= 1;
+                        }
+                        _ => {}
+                    }
+                    return;
+                }
+            };
+            if self.load(id).size() >= MIN_SIZE {
+                return;
+            }
+            let li = if idx > 0 { idx - 1 } else { idx };
+            let (_, children) = self.load(parent).internal();
+            let (left_id, right_id) = (children[li], children[li + 1]);
+            let mut left = self.take(left_id);
+            let mut right = self.take(right_id);
+            let (keys, children) = self.load(parent).internal();
+            let merge = left.size() + right.size() <= NODE_SIZE;
+            if merge {
+                let sep = keys.remove(li);
+                children.remove(li + 1);
+                match (&mut left, right) {
+                    (
+                        Node::Leaf { entries, next },
+                        Node::Leaf { entries: mut moved, next: last },
+                    ) => {
+                        entries.append(&mut moved);
+                        *next = last;
+                    }
+                    (
+                        Node::Internal { keys, children },
+                        Node::Internal { keys: mut moved_keys, children: mut moved_children },
+                    ) => {
+                        keys.push(sep);
+                        keys.append(&mut moved_keys);
+                        children.append(&mut moved_children);
+                    }
+                    _ => panic!("btree siblings of different kinds"),
+                }
+                self.free(right_id);
+            } else {
+                let sep = &mut keys[li];
+                match (&mut left, &mut right) {
+                    (Node::Leaf { entries: l, .. }, Node::Leaf { entries: r, .. }) => {
+                        if l.len() < r.len() {
+                            l.push(r.remove(0));
+                        } else {
+                            r.insert(0, l.pop().unwrap());
+                        }
+                        *sep = r[0].0.clone();
+                    }
+                    (
+                        Node::Internal { keys: lk, children: lc },
+                        Node::Internal { keys: rk, children: rc },
+                    ) => {
+                        if lc.len() < rc.len() {
+                            lk.push(core::mem::replace(sep, rk.remove(0)));
+                            lc.push(rc.remove(0));
+                        } else {
+                            rk.insert(0, core::mem::replace(sep, lk.pop().unwrap()));
+                            rc.insert(0, lc.pop().unwrap());
+                        }
+                    }
+                    _ => panic!("btree siblings of different kinds"),
+                }
+                self.put(right_id, right);
+            }
+            self.put(left_id, left);
+            self.mark_dirty(parent);
+            if !merge {
+                return;
+            }
+            id = parent;
+        }
+    }
+
+    //normalize position so that it points to an existing entry
+    fn seek(&mut self, mut id: u32, mut pos: usize) -> Option<(u32, usize)> {
+        loop {
+            match self.load(id) {
+                Node::Leaf { entries, .. } if pos < entries.len() => return Some((id, pos)),
+                Node::Leaf { next, .. } => id = (*next)?,
+                Node::Internal { .. } => panic!("btree leaf node expected"),
+            }
+            pos = 0;
+        }
+    }
+
+    fn bound_position(&mut self, key: &K, inclusive: bool) -> Option<(u32, usize)> {
+        let (_, id) = self.find_leaf(key)?;
+        let pos = match self.load(id).entries().binary_search_by(|(k, _)| k.cmp(key)) {
+            Ok(pos) if inclusive => pos,
+            Ok(pos) => pos + 1,
+            Err(pos) => pos,
+        };
+        self.seek(id, pos)
+    }
+
+    fn entry(&self, (id, pos): (u32, usize)) -> (&K, &V) {
+        match &self.cache[&id] {
+            Node::Leaf { entries, .. } => (&entries[pos].0, &entries[pos].1),
+            Node::Internal { .. } => panic!("btree leaf node expected"),
+        }
+    }
+
+    ///First entry whose key is not less than `key`.
+    pub fn lower_bound(&mut self, key: &K) -> Option<(&K, &V)> {
+        let position = self.bound_position(key, true)?;
+        Some(self.entry(position))
+    }
+
+    ///First entry whose key is greater than `key`.
+    pub fn upper_bound(&mut self, key: &K) -> Option<(&K, &V)> {
+        let position = self.bound_position(key, false)?;
+        Some(self.entry(position))
+    }
+
+    pub fn first(&mut self) -> Option<(&K, &V)> {
+        let id = self.edge_leaf(false)?;
+        let position = self.seek(id, 0)?;
+        Some(self.entry(position))
+    }
+
+    pub fn last(&mut self) -> Option<(&K, &V)> {
+        let id = self.edge_leaf(true)?;
+        let pos = self.load(id).size().checked_sub(1)?;
+        Some(self.entry((id, pos)))
+    }
+
+    pub fn iter(&mut self) -> Iter<K, V> {
+        self.range(..)
+    }
+
+    ///Iterate over entries in ascending key order within `range`.
+    pub fn range<R: RangeBounds<K>>(&mut self, range: R) -> Iter<K, V> {
+        let position = match range.start_bound() {
+            Bound::Included(key) => self.bound_position(key, true),
+            Bound::Excluded(key) => self.bound_position(key, false),
+            Bound::Unbounded => self.edge_leaf(false).and_then(|id| self.seek(id, 0)),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(key) => Bound::Included(key.clone()),
+            Bound::Excluded(key) => Bound::Excluded(key.clone()),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        Iter { store: self, position, end }
+    }
+
+    pub fn clear(&mut self) {
+        if let Some(root) = self.root {
+            //the ids of the leaves are found in their parents, only internal nodes are loaded
+            let mut level = vec![root];
+            for _ in 0..self.height {
+                let mut children = Vec::new();
+                for &id in level.iter() {
+                    children.extend_from_slice(self.load(id).internal().1);
+                    self.free(id);
+                }
+                level = children;
+            }
+            for id in level {
+                self.free(id);
+            }
+        }
+        self.len = 0;
+        self.next_node_id = 0;
+        self.root = None;
+        self.height = 0;
+        self.cache.clear();
+        self.need_flush.clear();
+        self.header_dirty = true;
+    }
+}
+
+impl<K: Encoder, V: Encoder> BTreeStore<K, V> {
+    pub fn flush(&mut self) {
+        //deleted first, `clear` lets the ids of deleted nodes be reused by new ones
+        for id in self.need_delete.iter() {
+            database::delete(gen_key(&self.key, *id));
+        }
+        for id in self.need_flush.iter() {
+            database::put(gen_key(&self.key, *id), &self.cache[id]);
+        }
+        if self.header_dirty {
+            database::put(&self.key, (self.len, self.next_node_id, self.root, self.height));
+        }
+        self.need_flush.clear();
+        self.need_delete.clear();
+        self.header_dirty = false;
+    }
+}
+
+///Iterator over the entries of a `BTreeStore` in ascending key order, which yields copies of them
+///as the nodes are loaded into the cache of the store on the way.
+pub struct Iter<'a, K: Encoder, V: Encoder> {
+    store: &'a mut BTreeStore<K, V>,
+    position: Option<(u32, usize)>,
+    end: Bound<K>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V>
+where
+    for<'b> K: Decoder<'b> + Encoder + Ord + Clone + 'static,
+    for<'b> V: Decoder<'b> + Encoder + Clone + 'static,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        let (id, pos) = self.position?;
+        let (key, val) = self.store.entry((id, pos));
+        let in_range = match self.end {
+            Bound::Included(ref end) => key <= end,
+            Bound::Excluded(ref end) => key < end,
+            Bound::Unbounded => true,
+        };
+        if !in_range {
+            self.position = None;
+            return None;
+        }
+        let item = (key.clone(), val.clone());
+        self.position = self.store.seek(id, pos + 1);
+        Some(item)
+    }
+}
+
+#[test]
+fn test_btree() {
+    let mut store: BTreeStore<u32, String> = BTreeStore::open(b"btree");
+    for i in (0..200).rev() {
+        assert_eq!(store.insert(i * 2, format!("v{}", i * 2)), None);
+    }
+    assert_eq!(store.len(), 200);
+    assert_eq!(store.height, 1);
+    assert_eq!(store.insert(10, "ten".to_string()), Some("v10".to_string()));
+    assert_eq!(store.get(&10).unwrap(), "ten");
+    assert_eq!(store.get(&11), None);
+
+    assert_eq!(store.lower_bound(&11).map(|(k, _)| *k), Some(12));
+    assert_eq!(store.lower_bound(&12).map(|(k, _)| *k), Some(12));
+    assert_eq!(store.upper_bound(&12).map(|(k, _)| *k), Some(14));
+    assert_eq!(store.upper_bound(&398).map(|(k, _)| *k), None);
+    assert_eq!(store.first().map(|(k, _)| *k), Some(0));
+    assert_eq!(store.last().map(|(k, _)| *k), Some(398));
+
+    let keys: Vec<u32> = store.range(100..=110).map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![100, 102, 104, 106, 108, 110]);
+    assert_eq!(store.range(397..).count(), 1);
+
+    assert_eq!(store.remove(&10), Some("ten".to_string()));
+    assert_eq!(store.remove(&10), None);
+    store.flush();
+
+    let mut store2: BTreeStore<u32, String> = BTreeStore::open(b"btree");
+    assert_eq!(store2.len(), 199);
+    assert_eq!(store2.get(&10), None);
+    assert_eq!(store2.get(&12).unwrap(), "v12");
+    store2.clear();
+    drop(store2);
+    let store3: BTreeStore<u32, String> = BTreeStore::open(b"btree");
+    assert!(store3.is_empty());
+}
+
+#[test]
+fn test_btree_levels() {
+    let mut store: BTreeStore<u32, u32> = BTreeStore::open(b"levels");
+    for i in 0..20000 {
+        store.insert(i, i);
+    }
+    assert_eq!(store.height, 2);
+    //scattered removals to borrow from and merge with siblings on both sides
+    for i in (0..20000u32).map(|i| i * 7919 % 20000).filter(|i| i % 50 != 0) {
+        assert_eq!(store.remove(&i), Some(i));
+    }
+    assert_eq!(store.height, 1);
+    assert_eq!(
+        store.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+        (0..400).map(|i| i * 50).collect::<Vec<_>>()
+    );
+    for i in (0..20000).step_by(50) {
+        assert_eq!(store.remove(&i), Some(i));
+    }
+    assert_eq!((store.height, store.root), (0, None));
+    assert!(store.cache.is_empty());
+}
+
+#[cfg(feature = "storage-stats")]
+#[test]
+fn test_btree_write_cost() {
+    let mut store: BTreeStore<u32, u32> = BTreeStore::open(b"cost");
+    for i in 0..20000 {
+        store.insert(i * 2, i);
+    }
+    store.flush();
+    database::reset_stats();
+    store.insert(1001, 0);
+    store.remove(&3000);
+    store.flush();
+    //the header and the leaves of both keys, the size of the root does not matter
+    let stats = database::stats();
+    assert_eq!((stats.writes, stats.deletes), (3, 0));
+    assert!(stats.written_bytes < 2 * (NODE_SIZE as u64) * 8 + 64);
+}
+
+#[test]
+fn mock_test_btree() {
+    use alloc::collections::BTreeMap;
+    for n in 0..20 {
+        let mut expected = BTreeMap::new();
+        for _ in 0..10 {
+            let mut store: BTreeStore<u16, u64> = BTreeStore::open(&[n]);
+            for _ in 0..300 {
+                let key = rand::random::<u16>() % 1000;
+                match rand::random::<u8>() % 4 {
+                    0 | 1 => {
+                        let val = rand::random();
+                        assert_eq!(store.insert(key, val), expected.insert(key, val));
+                    }
+                    2 => assert_eq!(store.remove(&key), expected.remove(&key)),
+                    _ => {
+                        let found = store.lower_bound(&key).map(|(k, v)| (*k, *v));
+                        let exp = expected.range(key..).next().map(|(k, v)| (*k, *v));
+                        assert_eq!(found, exp);
+                    }
+                }
+            }
+            assert_eq!(store.len() as usize, expected.len());
+            let all: Vec<(u16, u64)> = store.iter().collect();
+            assert_eq!(all, expected.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>());
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::abi::{Decoder, Source};
+use crate::prelude::*;
+use crate::runtime;
+use core::marker::PhantomData;
+
+///Iterate over all entries whose key starts with `prefix` in ascending key order.
+///
+///Each item is the full storage key and the decoded value. The iterator only remembers the last visited key,
+///so entries can be updated or deleted while iterating. Only available with the `storage-iter` feature,
+///see `runtime::storage_next_key`.
+///
+///# Example
+///```no_run
+/// # use ontio_std::database;
+/// # use ontio_std::types::U128;
+///   let total: U128 = database::iter_prefix::<U128>(b"balance").map(|(_key, balance)| balance).sum();
+///```
+pub fn iter_prefix<T>(prefix: &[u8]) -> PrefixIter<T>
+where
+    for<'a> T: Decoder<'a> + 'static,
+{
+    PrefixIter { prefix: prefix.to_vec(), last: None, _marker: PhantomData }
+}
+
+pub struct PrefixIter<T> {
+    prefix: Vec<u8>,
+    last: Option<Vec<u8>>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Iterator for PrefixIter<T>
+where
+    for<'a> T: Decoder<'a> + 'static,
+{
+    type Item = (Vec<u8>, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let after = self.last.as_deref().unwrap_or_default();
+            let key = runtime::storage_next_key(&self.prefix, after)?;
+            let val = runtime::storage_read(&key);
+            self.last = Some(key);
+            if let Some(val) = val {
+                let mut source = Source::new(&val);
+                let val = source.read().unwrap();
+                return Some((self.last.clone().unwrap(), val));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_iter_prefix() {
+    use crate::database;
+
+    database::put(b"a", 0u32);
+    database::put(b"b2", 2u32);
+    database::put(b"b", 0u32);
+    database::put(b"b1", 1u32);
+    database::put(b"b3", 3u32);
+    database::put(b"c", 0u32);
+
+    let items: Vec<(Vec<u8>, u32)> = iter_prefix(b"b").collect();
+    assert_eq!(
+        items,
+        vec![(b"b".to_vec(), 0), (b"b1".to_vec(), 1), (b"b2".to_vec(), 2), (b"b3".to_vec(), 3)]
+    );
+
+    for (key, val) in iter_prefix::<u32>(b"b") {
+        if val % 2 == 1 {
+            database::delete(key);
+        }
+    }
+    let values: Vec<u32> = iter_prefix(b"b").map(|(_, val)| val).collect();
+    assert_eq!(values, vec![0, 2]);
+    assert_eq!(iter_prefix::<u32>(b"d").count(), 0);
+    assert_eq!(iter_prefix::<u32>(b"").count(), 4);
+
+    let long_key = [b'l'; 100];
+    database::put(&long_key[..], 100u32);
+    assert_eq!(iter_prefix::<u32>(b"l").collect::<Vec<_>>(), vec![(long_key.to_vec(), 100)]);
+}
@@ -9,11 +9,15 @@ const INDEX_SIZE: u32 = 64;
 
 pub struct ListStore<T: Encoder> {
     key: Vec<u8>,
-    need_flush: Vec<u32>, //index,store all index which slice need update
+    need_flush: Vec<u32>,  //index,store all index which slice need update
+    need_delete: Vec<u32>, //index of the dropped slices, deleted on flush with the header rewrite
     size: u32,
     next_key_id: u32,
     index_size: Vec<(u32, u32)>,  //index, count
     cache: BTreeMap<u32, Vec<T>>, //index, vec
+    chunk_size: u32,
+    merge_threshold: u32, //slices holding fewer elements are merged on remove, 0 disables
+    header_dirty: bool,
 }
 
 impl<T: Encoder> Drop for ListStore<T> {
@@ -60,10 +64,14 @@ where
         Ok(ListStore {
             key,
             need_flush: Vec::new(), //index,store all index which slice need update
+            need_delete: Vec::new(),
             size: total,
             next_key_id,
             index_size,             //index, count
             cache: BTreeMap::new(), //index, vec
+            chunk_size: INDEX_SIZE,
+            merge_threshold: 0,
+            header_dirty: false,
         })
     }
 
@@ -71,18 +79,125 @@ where
         let need_flush: Vec<u32> = Vec::default();
         let index_size: Vec<(u32, u32)> = Vec::new();
         let cache: BTreeMap<u32, Vec<T>> = BTreeMap::new();
-        ListStore { key, need_flush, size: 0, next_key_id: 0, index_size, cache }
+        ListStore {
+            key,
+            need_flush,
+            need_delete: Vec::new(),
+            size: 0,
+            next_key_id: 0,
+            index_size,
+            cache,
+            chunk_size: INDEX_SIZE,
+            merge_threshold: 0,
+            header_dirty: false,
+        }
     }
     pub fn open(key: &[u8]) -> ListStore<T> {
-        match database::get::<_, Vec<u8>>(key) {
+        ListStore::open_with_chunk_size(key, INDEX_SIZE)
+    }
+
+    ///Open the list with at most `chunk_size` elements stored under each storage key.
+    ///
+    ///The chunk size is not persisted, it only decides how elements pushed from now on are grouped,
+    ///so a list can be reopened with a different value.
+    ///
+    ///# Example
+    ///```no_run
+    /// # use ontio_std::database::ListStore;
+    ///   let mut list: ListStore<u64> = ListStore::open_with_chunk_size(b"list", 16);
+    ///   list.set_merge_threshold(4);
+    ///   list.push(1);
+    ///```
+    pub fn open_with_chunk_size(key: &[u8], chunk_size: u32) -> ListStore<T> {
+        assert!(chunk_size > 0, "chunk size must be positive");
+        let mut list = match database::get::<_, Vec<u8>>(key) {
             None => ListStore::new(key.to_vec()),
             Some(data) => {
                 let mut source = Source::new(&data);
                 ListStore::init(key.to_vec(), &mut source).unwrap()
             }
+        };
+        list.chunk_size = chunk_size;
+        list
+    }
+
+    pub fn chunk_size(&self) -> u32 {
+        self.chunk_size
+    }
+
+    ///Rebalance a slice on `remove` once it holds fewer than `threshold` elements: an empty slice is dropped,
+    ///otherwise it is merged with a neighbour if their elements fit in one chunk.
+    ///With the default threshold of 0 `remove` never rebalances, empty slices are kept until `compact`.
+    pub fn set_merge_threshold(&mut self, threshold: u32) {
+        self.merge_threshold = threshold;
+    }
+
+    ///Merge neighbouring slices whose elements fit in one chunk and drop empty slices,
+    ///the storage keys of the dropped slices are deleted on flush, when the header is rewritten.
+    pub fn compact(&mut self) {
+        let mut ind = 0;
+        while ind < self.index_size.len() {
+            if self.index_size[ind].1 == 0 {
+                self.remove_slice(ind);
+            } else if ind + 1 < self.index_size.len()
+                && self.index_size[ind].1 + self.index_size[ind + 1].1 <= self.chunk_size
+            {
+                self.merge_next(ind);
+            } else {
+                ind += 1;
+            }
+        }
+    }
+
+    //merge the slice at `ind` with a neighbour if it became too small
+    fn rebalance(&mut self, ind: usize) {
+        let count = self.index_size[ind].1;
+        if count == 0 {
+            self.remove_slice(ind);
+        } else if ind + 1 < self.index_size.len()
+            && count + self.index_size[ind + 1].1 <= self.chunk_size
+        {
+            self.merge_next(ind);
+        } else if ind > 0 && self.index_size[ind - 1].1 + count <= self.chunk_size {
+            self.merge_next(ind - 1);
         }
     }
 
+    //move all elements of the slice at `ind + 1` to the end of the slice at `ind`
+    fn merge_next(&mut self, ind: usize) {
+        let (dst, _) = self.index_size[ind];
+        let (src, count) = self.index_size[ind + 1];
+        if count != 0 {
+            let mut moved = core::mem::take(self.load(src));
+            self.load(dst).append(&mut moved);
+            self.mark_dirty(dst);
+            self.index_size[ind].1 += count;
+        }
+        self.remove_slice(ind + 1);
+    }
+
+    //move the second half of the slice at `ind` to a new slice following it
+    fn split(&mut self, ind: usize) {
+        let (key_id, count) = self.index_size[ind];
+        let half = count / 2;
+        let moved = self.load(key_id).split_off(half as usize);
+        let new_id = self.next_key_id;
+        self.next_key_id += 1;
+        self.cache.insert(new_id, moved);
+        self.mark_dirty(new_id);
+        self.index_size[ind].1 = half;
+        self.index_size.insert(ind + 1, (new_id, count - half));
+    }
+
+    //drop the slice at `ind` from the index, its elements are discarded and its storage key deleted on flush
+    fn remove_slice(&mut self, ind: usize) {
+        let (key_id, _) = self.index_size.remove(ind);
+        self.cache.remove(&key_id);
+        self.need_flush.retain(|&k| k != key_id);
+        self.need_delete.push(key_id);
+        self.header_dirty = true;
+    }
+
     pub fn len(&self) -> u32 {
         self.size
     }
@@ -140,6 +255,9 @@ where
             bulk.1 -= 1;
             //update list size
             self.size -= 1;
+            if bulk.1 < self.merge_threshold {
+                self.rebalance(ind);
+            }
             val
         }
     }
@@ -173,7 +291,7 @@ where
             if let Some(last_node_vec) = self.cache.get_mut(&last_index_count.0) {
                 //if the slice is filled
                 let l = last_node_vec.len() as u32;
-                if l >= INDEX_SIZE {
+                if l >= self.chunk_size {
                     //cache add new k->v
                     self.cache.insert(self.next_key_id, vec![payload]);
                     self.index_size.push((self.next_key_id, 1));
@@ -195,53 +313,100 @@ where
     }
 
     pub fn insert(&mut self, index: u32, payload: T) {
+        if index > self.size {
+            panic!("[insert] index out of bound");
+        } else if index == self.size {
+            self.push(payload);
+        } else {
+            let (ind, start) = self.locate(index);
+            let key_id = self.index_size[ind].0;
+            self.load(key_id).insert((index - start) as usize, payload);
+            self.mark_dirty(key_id);
+            self.index_size[ind].1 += 1;
+            self.size += 1;
+            if self.index_size[ind].1 > self.chunk_size {
+                self.split(ind);
+            }
+        }
+    }
+
+    ///Replace the element at `index`, return the old one.
+    pub fn set(&mut self, index: u32, payload: T) -> T {
+        if index >= self.size {
+            panic!("[set] index out of bound");
+        }
+        let (ind, start) = self.locate(index);
+        let key_id = self.index_size[ind].0;
+        self.mark_dirty(key_id);
+        core::mem::replace(&mut self.load(key_id)[(index - start) as usize], payload)
+    }
+
+    ///Remove the element at `index` and replace it with the last element, this does not preserve ordering.
+    pub fn swap_remove(&mut self, index: u32) -> T {
         if index >= self.size {
-            panic!("index out of bound");
+            panic!("[swap_remove] index out of bound");
+        }
+        let last = self.pop().unwrap();
+        if index == self.size {
+            last
         } else {
-            let end = &mut 0;
-            let ind = self
-                .index_size
-                .iter()
-                .take_while(|&x| {
-                    *end += x.1;
-                    *end <= index
-                })
-                .count();
-            let bulk = &mut self.index_size[ind];
-            let start = *end - bulk.1;
-            if self.cache.contains_key(&bulk.0) {
-                //if data in cache
-                let temp = self.cache.get_mut(&bulk.0).unwrap();
-                temp.insert((index - start) as usize, payload);
-            } else {
-                //read data from db
-                let key: Vec<u8> = gen_key(self.key.as_slice(), bulk.0);
-                match database::get::<_, Vec<u8>>(key.as_slice()) {
-                    Some(data) => {
-                        let mut source = Source::new(&data);
-                        let l = source.read_u32().unwrap();
-                        let mut temp: Vec<T> = Vec::new();
-                        for _ in 0..l {
-                            temp.push(source.read().unwrap());
-                        }
-                        temp.insert((index - start) as usize, payload);
-                        self.cache.insert(index, temp);
-                    }
-                    None => unreachable!(),
-                }
+            self.set(index, last)
+        }
+    }
+
+    ///Get a mutable reference of the element at `index`, the slice containing it will be flushed.
+    ///Return `None` if `index` is out of bound.
+    pub fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        if index >= self.size {
+            return None;
+        }
+        let (ind, start) = self.locate(index);
+        let key_id = self.index_size[ind].0;
+        self.mark_dirty(key_id);
+        self.load(key_id).get_mut((index - start) as usize)
+    }
+
+    //return the position in index_size of the slice containing `index`, and the index of its first element
+    fn locate(&self, index: u32) -> (usize, u32) {
+        let mut end = 0;
+        let ind = self
+            .index_size
+            .iter()
+            .take_while(|&x| {
+                end += x.1;
+                end <= index
+            })
+            .count();
+        (ind, end - self.index_size[ind].1)
+    }
+
+    fn load(&mut self, key_id: u32) -> &mut Vec<T> {
+        let key = &self.key;
+        self.cache.entry(key_id).or_insert_with(|| {
+            let data: Vec<u8> = database::get(gen_key(key.as_slice(), key_id)).unwrap();
+            let mut source = Source::new(&data);
+            let l = source.read_u32().unwrap();
+            let mut temp: Vec<T> = Vec::new();
+            for _ in 0..l {
+                temp.push(source.read().unwrap());
             }
-            bulk.1 += 1;
-            self.size += 1;
+            temp
+        })
+    }
+
+    fn mark_dirty(&mut self, key_id: u32) {
+        if !self.need_flush.contains(&key_id) {
+            self.need_flush.push(key_id);
         }
     }
 
     pub fn clear(&mut self) {
         let index_size = self.index_size.to_vec();
         for bulk in index_size {
-            let key: Vec<u8> = gen_key(self.key.as_slice(), bulk.0);
-            database::delete(key.as_slice());
+            self.need_delete.push(bulk.0);
         }
         self.need_flush.clear();
+        self.header_dirty = true;
         self.next_key_id = 0;
         self.index_size.clear();
         self.size = 0;
@@ -285,12 +450,18 @@ where
 
 impl<T: Encoder> ListStore<T> {
     pub fn flush(&mut self) {
-        if !self.need_flush.is_empty() {
+        if !self.need_flush.is_empty() || self.header_dirty {
+            //deleted first, `clear` lets the ids of dropped slices be reused by new ones
+            for k in self.need_delete.iter() {
+                database::delete(gen_key(self.key.as_slice(), *k));
+            }
             let need_flush = self.need_flush.to_vec();
             for k in need_flush {
                 let v = self.cache.get(&k).unwrap();
                 let l = v.len() as u32;
-                let mut sink = Sink::new(16);
+                let mut sink = Sink::new(
+                    v.iter().map(T::encoded_len).sum::<Option<usize>>().map_or(16, |len| 4 + len),
+                );
                 sink.write_u32(l);
                 for i in v {
                     i.encode(&mut sink);
@@ -300,7 +471,10 @@ impl<T: Encoder> ListStore<T> {
             }
             let mut sink = Sink::new(16);
             self.encode(&mut sink);
-            database::put(&self.key, sink.bytes())
+            database::put(&self.key, sink.bytes());
+            self.need_flush.clear();
+            self.need_delete.clear();
+            self.header_dirty = false;
         }
     }
 }
@@ -405,6 +579,120 @@ fn test_iter() {
     }
 }
 
+#[test]
+fn test_set_swap_remove() {
+    let mut list: ListStore<String> = ListStore::open(b"key");
+    for x in 0..90 {
+        list.push(format!("hello{}", x));
+    }
+    list.flush();
+    drop(list);
+
+    let mut list: ListStore<String> = ListStore::open(b"key");
+    assert_eq!(list.set(70, "world70".to_string()), "hello70");
+    list.insert(65, "world65".to_string());
+    list.insert(91, "world91".to_string());
+    list.get_mut(1).unwrap().push_str("!");
+    assert!(list.get_mut(list.len()).is_none());
+    assert_eq!(list.swap_remove(0), "hello0");
+    assert_eq!(list.swap_remove(90), "hello89");
+    list.flush();
+    drop(list);
+
+    let mut list: ListStore<String> = ListStore::open(b"key");
+    assert_eq!(list.len(), 90);
+    assert_eq!(list.get(0).unwrap(), "world91");
+    assert_eq!(list.get(1).unwrap(), "hello1!");
+    assert_eq!(list.get(65).unwrap(), "world65");
+    assert_eq!(list.get(71).unwrap(), "world70");
+    assert_eq!(list.get(89).unwrap(), "hello88");
+}
+
+#[test]
+fn test_compact() {
+    let mut list: ListStore<u32> = ListStore::open_with_chunk_size(b"key", 16);
+    for x in 0..100 {
+        list.push(x);
+    }
+    list.flush();
+    assert_eq!(list.index_size.len(), 7);
+    for x in (0..100).rev().filter(|x| x % 4 != 0) {
+        list.remove(x);
+    }
+    for _ in 0..4 {
+        list.remove(0);
+    }
+    assert_eq!(list.index_size.iter().filter(|x| x.1 == 0).count(), 1);
+    list.compact();
+    assert_eq!(list.index_size.iter().map(|x| x.1).collect::<Vec<_>>(), vec![16, 5]);
+    //the dropped slices are still referenced by the stored header until it is flushed
+    let reopened: ListStore<u32> = ListStore::open(b"key");
+    assert_eq!(reopened.index_size.len(), 7);
+    for &key_id in [0, 2, 3, 4, 6].iter() {
+        assert!(database::get::<_, Vec<u8>>(gen_key(b"key", key_id)).is_some());
+    }
+    drop(reopened);
+    list.flush();
+    drop(list);
+    for &key_id in [0, 2, 3, 4, 6].iter() {
+        assert!(database::get::<_, Vec<u8>>(gen_key(b"key", key_id)).is_none());
+    }
+
+    let mut list: ListStore<u32> = ListStore::open_with_chunk_size(b"key", 16);
+    assert_eq!(list.len(), 21);
+    assert_eq!(list.index_size.len(), 2);
+    for x in 0..21 {
+        assert_eq!(*list.get(x).unwrap(), (x + 4) * 4);
+    }
+    list.clear();
+    drop(list);
+    let list: ListStore<u32> = ListStore::open(b"key");
+    assert!(list.is_empty());
+}
+
+#[test]
+fn test_insert_split() {
+    let mut list: ListStore<u32> = ListStore::open_with_chunk_size(b"key", 8);
+    for x in 0..16 {
+        list.push(x * 2);
+    }
+    for x in 0..8 {
+        list.insert(x * 2 + 1, x * 2 + 1);
+    }
+    assert_eq!(list.index_size.iter().map(|x| x.1).collect::<Vec<_>>(), vec![5, 4, 6, 4, 5]);
+    list.flush();
+    drop(list);
+
+    let mut list: ListStore<u32> = ListStore::open_with_chunk_size(b"key", 8);
+    let all: Vec<u32> = (0..list.len()).map(|x| *list.get(x).unwrap()).collect();
+    let expect: Vec<u32> = (0..16).chain((8..16).map(|x| x * 2)).collect();
+    assert_eq!(all, expect);
+}
+
+#[test]
+fn test_merge_threshold() {
+    let mut list: ListStore<u32> = ListStore::open_with_chunk_size(b"key", 8);
+    list.set_merge_threshold(3);
+    for x in 0..32 {
+        list.push(x);
+    }
+    for _ in 0..6 {
+        list.remove(8);
+    }
+    assert_eq!(list.index_size.iter().map(|x| x.1).collect::<Vec<_>>(), vec![8, 2, 8, 8]);
+    for _ in 0..6 {
+        list.remove(10);
+    }
+    assert_eq!(list.index_size.iter().map(|x| x.1).collect::<Vec<_>>(), vec![8, 4, 8]);
+    for _ in 0..4 {
+        list.remove(8);
+    }
+    assert_eq!(list.index_size.iter().map(|x| x.1).collect::<Vec<_>>(), vec![8, 8]);
+    let all: Vec<u32> = (0..list.len()).map(|x| *list.get(x).unwrap()).collect();
+    let expect: Vec<u32> = (0..8).chain(24..32).collect();
+    assert_eq!(all, expect);
+}
+
 #[test]
 fn clear() {
     let mut list: ListStore<String> = ListStore::open(b"key");
@@ -458,8 +746,71 @@ fn mock_test() {
                     }
                     assert_eq!(temp, array);
                 }
+                201..220 => {
+                    if array.len() != 0 {
+                        let val = rand::random();
+                        let pos = rand::random::<usize>() % array.len();
+                        assert_eq!(list.set(pos as u32, val), array[pos]);
+                        array[pos] = val;
+                    }
+                }
+                221..240 => {
+                    if array.len() != 0 {
+                        let pos = rand::random::<usize>() % array.len();
+                        assert_eq!(list.swap_remove(pos as u32), array.swap_remove(pos));
+                    }
+                }
                 _ => (),
             }
         }
     }
 }
+
+#[test]
+fn mock_test_compact() {
+    for _n in 0..200 {
+        let chunk_size = rand::random::<u32>() % 8 + 1;
+        let threshold = rand::random::<u32>() % (chunk_size + 1);
+        let mut list: ListStore<u64> = ListStore::open_with_chunk_size(b"key", chunk_size);
+        list.set_merge_threshold(threshold);
+        list.clear();
+        let mut array = Vec::new();
+        for _i in 0..200 {
+            match rand::random::<u8>() {
+                0..80 => {
+                    let val = rand::random();
+                    list.push(val);
+                    array.push(val);
+                }
+                80..120 => {
+                    if array.len() != 0 {
+                        let val = rand::random();
+                        let pos = rand::random::<usize>() % array.len();
+                        list.insert(pos as u32, val);
+                        array.insert(pos, val);
+                    }
+                }
+                120..200 => {
+                    if array.len() != 0 {
+                        let pos = rand::random::<usize>() % array.len();
+                        assert_eq!(list.remove(pos as u32), array.remove(pos));
+                    }
+                }
+                200..220 => list.compact(),
+                220..230 => {
+                    list.flush();
+                    list = ListStore::open_with_chunk_size(b"key", chunk_size);
+                    list.set_merge_threshold(threshold);
+                }
+                _ => {
+                    let all: Vec<u64> = (0..list.len()).map(|x| *list.get(x).unwrap()).collect();
+                    assert_eq!(all, array);
+                }
+            }
+        }
+        list.compact();
+        assert!(list.index_size.windows(2).all(|x| x[0].1 + x[1].1 > chunk_size));
+        let all: Vec<u64> = (0..list.len()).map(|x| *list.get(x).unwrap()).collect();
+        assert_eq!(all, array);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::abi::{Decoder, Encoder, Sink};
+use crate::database;
+use crate::prelude::*;
+use crate::runtime;
+use core::marker::PhantomData;
+
+///Key-value mapping stored under a prefix.
+///
+///The storage key of an entry is the prefix encoded as bytearray followed by the encoded key,
+///so maps with different prefixes never share keys.
+///
+///# Example
+///```no_run
+/// # use ontio_std::database::StorageMap;
+/// # use ontio_std::types::{Address, U128};
+///   let balances: StorageMap<Address, U128> = StorageMap::new(b"balance");
+///   let owner = Address::repeat_byte(1);
+///   balances.insert(&owner, U128::new(100));
+///   assert_eq!(balances.get(&owner), Some(U128::new(100)));
+///```
+pub struct StorageMap<K, V> {
+    prefix: Vec<u8>,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<K: Encoder, V> StorageMap<K, V>
+where
+    for<'a> V: Decoder<'a> + Encoder + 'static,
+{
+    pub fn new(prefix: &[u8]) -> Self {
+        StorageMap { prefix: prefix.to_vec(), _marker: PhantomData }
+    }
+
+    ///Storage key of `key` in this map.
+    pub fn storage_key(&self, key: &K) -> Vec<u8> {
+        Sink::with_value(&(self.prefix.as_slice(), key)).into()
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        database::get(self.storage_key(key))
+    }
+
+    pub fn insert(&self, key: &K, val: V) {
+        database::put(self.storage_key(key), val)
+    }
+
+    pub fn remove(&self, key: &K) {
+        database::delete(self.storage_key(key))
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        runtime::storage_read(&self.storage_key(key)).is_some()
+    }
+
+    ///Load the entry of `key` for in-place update, the change is written back when the entry is dropped.
+    ///
+    ///# Example
+    ///```no_run
+    /// # use ontio_std::database::StorageMap;
+    /// # use ontio_std::types::{Address, U128};
+    ///   let balances: StorageMap<Address, U128> = StorageMap::new(b"balance");
+    ///   let owner = Address::repeat_byte(1);
+    ///   *balances.entry(&owner).or_default() += U128::new(100);
+    ///```
+    pub fn entry(&self, key: &K) -> Entry<V> {
+        let key = self.storage_key(key);
+        let value = database::get(&key);
+        Entry { key, value, dirty: false }
+    }
+}
+
+///A view into a single entry of `StorageMap`, flushed on drop if modified.
+pub struct Entry<V: Encoder> {
+    key: Vec<u8>,
+    value: Option<V>,
+    dirty: bool,
+}
+
+impl<V: Encoder> Entry<V> {
+    pub fn get(&self) -> Option<&V> {
+        self.value.as_ref()
+    }
+
+    pub fn get_mut(&mut self) -> Option<&mut V> {
+        self.dirty = true;
+        self.value.as_mut()
+    }
+
+    ///Insert `default` if the entry is vacant and return the value, which is written back when the entry is dropped.
+    pub fn or_insert(&mut self, default: V) -> &mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(&mut self, default: F) -> &mut V {
+        self.dirty = true;
+        self.value.get_or_insert_with(default)
+    }
+
+    pub fn or_default(&mut self) -> &mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Some(val) = self.get_mut() {
+            f(val);
+        }
+        self
+    }
+
+    pub fn insert(&mut self, val: V) {
+        self.dirty = true;
+        self.value = Some(val);
+    }
+
+    pub fn remove(&mut self) -> Option<V> {
+        self.dirty = true;
+        self.value.take()
+    }
+
+    pub fn flush(&mut self) {
+        if self.dirty {
+            match self.value {
+                Some(ref val) => database::put(&self.key, val),
+                None => database::delete(&self.key),
+            }
+            self.dirty = false;
+        }
+    }
+}
+
+impl<V: Encoder> Drop for Entry<V> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[test]
+fn test_map() {
+    let map: StorageMap<u32, String> = StorageMap::new(b"map");
+    assert_eq!(map.get(&1), None);
+    assert!(!map.contains_key(&1));
+    map.insert(&1, "one".to_string());
+    map.insert(&2, "two".to_string());
+    assert_eq!(map.get(&1), Some("one".to_string()));
+    assert!(map.contains_key(&2));
+    map.remove(&2);
+    assert!(!map.contains_key(&2));
+
+    let other: StorageMap<u32, String> = StorageMap::new(b"ma");
+    assert_eq!(other.get(&1), None);
+    assert_ne!(map.storage_key(&1), other.storage_key(&1));
+}
+
+#[test]
+fn test_entry() {
+    let map: StorageMap<u32, U128> = StorageMap::new(b"entry");
+    *map.entry(&1).or_default() += U128::new(10);
+    //updates of an existing value through `or_default` are persisted
+    *map.entry(&1).or_default() += U128::new(5);
+    assert_eq!(map.get(&1), Some(U128::new(15)));
+
+    map.entry(&1).and_modify(|v| *v -= 1);
+    map.entry(&2).and_modify(|v| *v -= 1);
+    assert_eq!(map.get(&1), Some(U128::new(14)));
+    assert!(!map.contains_key(&2));
+
+    let mut entry = map.entry(&1);
+    assert_eq!(entry.remove(), Some(U128::new(14)));
+    drop(entry);
+    assert!(!map.contains_key(&1));
+}
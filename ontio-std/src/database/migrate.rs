@@ -0,0 +1,205 @@
+use crate::abi::{Decoder, Encoder};
+use crate::database;
+use crate::prelude::*;
+use alloc::collections::BTreeMap;
+
+///Storage key of the data layout version managed by `Migrator`.
+pub const SCHEMA_VERSION_KEY: &[u8] = b"__ontio_schema_version";
+
+///Version of the data layout currently stored, 0 if it has never been set.
+pub fn schema_version() -> u32 {
+    database::get(SCHEMA_VERSION_KEY).unwrap_or(0)
+}
+
+///Record the version of the data layout, a freshly deployed contract should call it with its latest version
+///so that no migration is run on it.
+pub fn set_schema_version(version: u32) {
+    database::put(SCHEMA_VERSION_KEY, version)
+}
+
+///Re-encode the value stored under `key` from type `Old` to type `New`, return false if the key does not exist.
+///
+///# Example
+///```no_run
+/// # use ontio_std::database;
+/// # use ontio_std::types::U128;
+///   database::migrate_value(b"total_supply", |supply: u64| U128::new(supply as u128));
+///```
+#[track_caller]
+pub fn migrate_value<K: AsRef<[u8]>, Old, New: Encoder, F: FnOnce(Old) -> New>(key: K, f: F) -> bool
+where
+    for<'a> Old: Decoder<'a> + 'static,
+{
+    match database::get(key.as_ref()) {
+        Some(old) => {
+            database::put(key, f(old));
+            true
+        }
+        None => false,
+    }
+}
+
+///Registry of storage migrations keyed by `(from_version, to_version)`.
+///
+///`migrate` reads the stored schema version and applies the registered migrations one after another
+///until the target version is reached, so it can be called at the beginning of every invocation:
+///once the layout is up to date it only costs one storage read.
+///
+///# Example
+///```no_run
+/// # use ontio_std::database::{self, Migrator};
+/// # use ontio_std::types::U128;
+///   fn supply_to_u128() {
+///       database::migrate_value(b"total_supply", |supply: u64| U128::new(supply as u128));
+///   }
+///   Migrator::new().register(0, 1, supply_to_u128).migrate(1);
+///```
+#[derive(Default)]
+pub struct Migrator {
+    migrations: BTreeMap<(u32, u32), fn()>,
+}
+
+impl Migrator {
+    pub fn new() -> Self {
+        Migrator { migrations: BTreeMap::new() }
+    }
+
+    ///Register the migration converting the layout of version `from` to version `to`.
+    pub fn register(mut self, from: u32, to: u32, migration: fn()) -> Self {
+        assert!(from < to, "migration must upgrade the schema version");
+        self.migrations.insert((from, to), migration);
+        self
+    }
+
+    ///Run the pending migrations up to version `target` and return the number of migrations applied.
+    ///
+    ///From each version the migration reaching furthest without passing `target` is chosen,
+    ///it panics if the target version can not be reached or if the stored version is newer than `target`.
+    pub fn migrate(&self, target: u32) -> u32 {
+        let mut version = schema_version();
+        if version > target {
+            panic!("[migrate] stored schema version {} is newer than {}", version, target);
+        }
+        let mut applied = 0;
+        while version < target {
+            let next = self
+                .migrations
+                .range((version, version)..=(version, target))
+                .next_back()
+                .map(|(&(_, to), migration)| (to, migration));
+            match next {
+                Some((to, migration)) => {
+                    migration();
+                    version = to;
+                    set_schema_version(version);
+                    applied += 1;
+                }
+                None => panic!("[migrate] no migration from schema version {}", version),
+            }
+        }
+        applied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::{Error, Sink, Source};
+
+    #[derive(Debug, PartialEq)]
+    struct AccountV1 {
+        owner: Address,
+        balance: u64,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct AccountV2 {
+        owner: Address,
+        balance: U128,
+        frozen: bool,
+    }
+
+    impl Encoder for AccountV1 {
+        fn encode(&self, sink: &mut Sink) {
+            sink.write(&self.owner);
+            sink.write(self.balance);
+        }
+    }
+
+    impl<'a> Decoder<'a> for AccountV1 {
+        fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+            Ok(AccountV1 { owner: source.read()?, balance: source.read()? })
+        }
+    }
+
+    impl Encoder for AccountV2 {
+        fn encode(&self, sink: &mut Sink) {
+            sink.write(&self.owner);
+            sink.write(self.balance);
+            sink.write(self.frozen);
+        }
+    }
+
+    impl<'a> Decoder<'a> for AccountV2 {
+        fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+            Ok(AccountV2 { owner: source.read()?, balance: source.read()?, frozen: source.read()? })
+        }
+    }
+
+    fn account_v2() {
+        migrate_value(b"account", |old: AccountV1| AccountV2 {
+            owner: old.owner,
+            balance: U128::new(old.balance as u128),
+            frozen: false,
+        });
+    }
+
+    fn rename_total() {
+        let total: u64 = database::get(b"total").unwrap();
+        database::delete(b"total");
+        database::put(b"total_supply", U128::new(total as u128));
+    }
+
+    fn migrator() -> Migrator {
+        Migrator::new().register(0, 1, account_v2).register(1, 2, rename_total)
+    }
+
+    #[test]
+    fn test_migrate() {
+        let owner = Address::repeat_byte(1);
+        database::put(b"account", AccountV1 { owner: owner.clone(), balance: 100 });
+        database::put(b"total", 100u64);
+        assert_eq!(schema_version(), 0);
+
+        assert_eq!(migrator().migrate(1), 1);
+        assert_eq!(schema_version(), 1);
+        let account: AccountV2 = database::get(b"account").unwrap();
+        assert_eq!(account, AccountV2 { owner, balance: U128::new(100), frozen: false });
+
+        assert_eq!(migrator().migrate(2), 1);
+        assert_eq!(migrator().migrate(2), 0);
+        assert_eq!(schema_version(), 2);
+        assert_eq!(database::get::<_, u64>(b"total"), None);
+        assert_eq!(database::get::<_, U128>(b"total_supply"), Some(U128::new(100)));
+    }
+
+    #[test]
+    fn test_migrate_shortcut() {
+        database::put(b"total", 7u64);
+        let migrator = migrator().register(0, 2, rename_total);
+        assert_eq!(migrator.migrate(2), 1);
+        assert_eq!(database::get::<_, U128>(b"total_supply"), Some(U128::new(7)));
+    }
+
+    #[test]
+    fn test_fresh_deploy() {
+        set_schema_version(2);
+        assert_eq!(migrator().migrate(2), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_missing_migration() {
+        Migrator::new().register(1, 2, rename_total).migrate(2);
+    }
+}
@@ -1,8 +1,32 @@
+mod btree;
+#[cfg(feature = "storage-iter")]
+mod iter;
 mod list;
+mod map;
+mod migrate;
+mod set;
+mod stats;
+mod transaction;
+mod value;
 
+pub use self::btree::BTreeStore;
+#[cfg(feature = "storage-iter")]
+pub use self::iter::{iter_prefix, PrefixIter};
 pub use self::list::ListStore;
+pub use self::map::{Entry, StorageMap};
+pub use self::migrate::{
+    migrate_value, schema_version, set_schema_version, Migrator, SCHEMA_VERSION_KEY,
+};
+pub use self::set::StorageSet;
+#[cfg(feature = "storage-stats")]
+pub(crate) use self::stats::with_stats;
+pub use self::stats::StorageStats;
+#[cfg(feature = "storage-stats")]
+pub use self::stats::{reset_stats, stats};
+pub use self::transaction::Transaction;
+pub use self::value::StorageValue;
 
-use super::abi::{Decoder, Encoder, Sink, Source};
+use super::abi::{Decoder, Encoder, Error, ErrorKind, Sink, Source};
 use super::prelude::*;
 use super::runtime;
 
@@ -16,12 +40,54 @@ where
     Some(source.read().unwrap())
 }
 
+///Same as `get`, but returns an error instead of panicking when the stored value can not be decoded
+///as `T` or is followed by trailing bytes.
+///
+///# Example
+///```no_run
+/// # use ontio_std::database;
+/// # use ontio_std::types::U128;
+///   let supply = match database::try_get::<_, U128>(b"total_supply") {
+///       Ok(supply) => supply.unwrap_or_default(),
+///       Err(_) => U128::new(database::get::<_, u64>(b"total_supply").unwrap() as u128),
+///   };
+///```
+pub fn try_get<K: AsRef<[u8]>, T>(key: K) -> Result<Option<T>, Error>
+where
+    for<'a> T: Decoder<'a> + 'static,
+{
+    let val = match runtime::storage_read(key.as_ref()) {
+        Some(val) => val,
+        None => return Ok(None),
+    };
+    let mut source = Source::new(&val);
+    let res = source.read()?;
+    if !source.is_empty() {
+        return Err(source.error(ErrorKind::TrailingBytes));
+    }
+    Ok(Some(res))
+}
+
 pub fn put<K: AsRef<[u8]>, T: Encoder>(key: K, val: T) {
-    let mut sink = Sink::new(12);
-    sink.write(val);
+    let sink = Sink::with_value(&val);
     runtime::storage_write(key.as_ref(), sink.bytes());
 }
 
 pub fn delete<K: AsRef<[u8]>>(key: K) {
     runtime::storage_delete(key.as_ref());
 }
+
+#[test]
+fn test_try_get() {
+    assert!(matches!(try_get::<_, u64>("value"), Ok(None)));
+    put("value", 1u64);
+    assert!(matches!(try_get::<_, u64>("value"), Ok(Some(1))));
+    assert!(
+        matches!(try_get::<_, u32>("value"), Err(ref e) if e.kind() == ErrorKind::TrailingBytes)
+    );
+    assert!(
+        matches!(try_get::<_, U128>("value"), Err(ref e) if e.kind() == ErrorKind::UnexpectedEOF)
+    );
+    put("value", "hello");
+    assert!(matches!(try_get::<_, String>("value"), Ok(Some(ref s)) if s == "hello"));
+}
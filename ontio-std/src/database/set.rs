@@ -0,0 +1,186 @@
+use super::list::{Iterator, ListStore};
+use crate::abi::{Decoder, Encoder, Sink};
+use crate::database;
+use crate::prelude::*;
+use crate::runtime;
+
+///Set of members stored under a prefix.
+///
+///Each member is stored under the prefix followed by the sha256 hash of its encoding,
+///so `insert`, `remove` and `contains` cost a single storage access whatever the size of the set.
+///An enumerable set additionally keeps its members in a `ListStore`, removal swaps the last member
+///into the freed position so it stays O(1) but does not preserve insertion order.
+///
+///# Example
+///```no_run
+/// # use ontio_std::database::StorageSet;
+/// # use ontio_std::types::Address;
+///   let mut admins: StorageSet<Address> = StorageSet::enumerable(b"admins");
+///   admins.insert(Address::repeat_byte(1));
+///   assert!(admins.contains(&Address::repeat_byte(1)));
+///   assert_eq!(admins.get(0), Some(&Address::repeat_byte(1)));
+///```
+pub struct StorageSet<T: Encoder> {
+    prefix: Vec<u8>,
+    len: u32,
+    list: Option<ListStore<T>>,
+}
+
+impl<T> StorageSet<T>
+where
+    for<'a> T: Decoder<'a> + Encoder + 'static,
+{
+    pub fn new(prefix: &[u8]) -> Self {
+        let prefix = prefix.to_vec();
+        let len = database::get(gen_key(&prefix, b"len")).unwrap_or(0);
+        StorageSet { prefix, len, list: None }
+    }
+
+    ///Open a set whose members can be enumerated, it must always be opened in this mode.
+    pub fn enumerable(prefix: &[u8]) -> Self {
+        let prefix = prefix.to_vec();
+        let list = ListStore::open(&gen_key(&prefix, b"list"));
+        StorageSet { prefix, len: list.len(), list: Some(list) }
+    }
+
+    ///Storage key of `member` in this set.
+    pub fn storage_key(&self, member: &T) -> Vec<u8> {
+        member_key(&self.prefix, member)
+    }
+
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, member: &T) -> bool {
+        runtime::storage_read(&self.storage_key(member)).is_some()
+    }
+
+    ///Add `member` to the set, return false if it is already present.
+    pub fn insert(&mut self, member: T) -> bool {
+        let key = self.storage_key(&member);
+        if runtime::storage_read(&key).is_some() {
+            return false;
+        }
+        //the value is the position of the member in the list, 0 for a non enumerable set
+        database::put(&key, self.len);
+        if let Some(list) = self.list.as_mut() {
+            list.push(member);
+        }
+        self.set_len(self.len + 1);
+        true
+    }
+
+    ///Remove `member` from the set, return false if it is not present.
+    pub fn remove(&mut self, member: &T) -> bool {
+        let key = self.storage_key(member);
+        let index: u32 = match database::get(&key) {
+            Some(index) => index,
+            None => return false,
+        };
+        database::delete(&key);
+        if let Some(list) = self.list.as_mut() {
+            list.swap_remove(index);
+            if index < list.len() {
+                //the last member was moved to `index`
+                database::put(member_key(&self.prefix, list.get(index).unwrap()), index);
+            }
+        }
+        self.set_len(self.len - 1);
+        true
+    }
+
+    ///Get the member at `index` of an enumerable set.
+    pub fn get(&mut self, index: u32) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.list.as_mut().expect("[get] set is not enumerable").get(index)
+    }
+
+    ///Iterate the members of an enumerable set.
+    pub fn iter(&mut self) -> Iterator<T> {
+        self.list.as_mut().expect("[iter] set is not enumerable").iter()
+    }
+
+    fn set_len(&mut self, len: u32) {
+        self.len = len;
+        if self.list.is_none() {
+            database::put(gen_key(&self.prefix, b"len"), len);
+        }
+    }
+}
+
+impl<T: Encoder> StorageSet<T> {
+    pub fn flush(&mut self) {
+        if let Some(list) = self.list.as_mut() {
+            list.flush();
+        }
+    }
+}
+
+fn member_key<T: Encoder>(prefix: &[u8], member: &T) -> Vec<u8> {
+    let sink = Sink::with_value(member);
+    gen_key(prefix, runtime::sha256(sink.bytes()).as_bytes())
+}
+
+//the prefix is encoded as bytearray, so the keys of sets with different prefixes never overlap
+fn gen_key(prefix: &[u8], post: &[u8]) -> Vec<u8> {
+    let mut sink = Sink::new(prefix.len() + post.len() + 1);
+    sink.write(prefix);
+    sink.write_bytes(post);
+    sink.into()
+}
+
+#[test]
+fn test_set() {
+    let mut set: StorageSet<String> = StorageSet::new(b"set");
+    assert!(set.insert("a".to_string()));
+    assert!(set.insert("b".to_string()));
+    assert!(!set.insert("a".to_string()));
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&"b".to_string()));
+    assert!(set.remove(&"b".to_string()));
+    assert!(!set.remove(&"b".to_string()));
+    assert!(!set.contains(&"b".to_string()));
+
+    let set: StorageSet<String> = StorageSet::new(b"set");
+    assert_eq!(set.len(), 1);
+    assert!(set.contains(&"a".to_string()));
+    let other: StorageSet<String> = StorageSet::new(b"se");
+    assert!(!other.contains(&"a".to_string()));
+}
+
+#[test]
+fn test_enumerable_set() {
+    let mut set: StorageSet<u64> = StorageSet::enumerable(b"set");
+    for x in 0..100 {
+        assert!(set.insert(x));
+    }
+    assert!(set.remove(&10));
+    assert!(set.remove(&99));
+    assert!(set.remove(&0));
+    drop(set);
+
+    let mut set: StorageSet<u64> = StorageSet::enumerable(b"set");
+    assert_eq!(set.len(), 97);
+    assert_eq!(set.get(0), Some(&97));
+    assert_eq!(set.get(10), Some(&98));
+    let mut members = Vec::new();
+    let mut iter = set.iter();
+    while let Some(x) = iter.next() {
+        members.push(*x);
+    }
+    members.sort();
+    let expect: Vec<u64> = (1..99).filter(|&x| x != 10).collect();
+    assert_eq!(members, expect);
+    for x in expect {
+        assert!(set.remove(&x));
+    }
+    assert!(set.is_empty());
+    assert_eq!(set.get(0), None);
+}
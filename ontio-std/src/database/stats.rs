@@ -0,0 +1,81 @@
+///Storage usage of the current invocation.
+///
+///Bytes are counted as key length plus value length, a read of a missing key and a delete only count the key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageStats {
+    pub reads: u32,
+    pub read_bytes: u64,
+    pub writes: u32,
+    pub written_bytes: u64,
+    pub deletes: u32,
+    pub deleted_bytes: u64,
+}
+
+impl StorageStats {
+    pub(crate) fn record_read(&mut self, key: &[u8], val: Option<&[u8]>) {
+        self.reads += 1;
+        self.read_bytes += (key.len() + val.map(|v| v.len()).unwrap_or(0)) as u64;
+    }
+
+    pub(crate) fn record_write(&mut self, key: &[u8], val: &[u8]) {
+        self.writes += 1;
+        self.written_bytes += (key.len() + val.len()) as u64;
+    }
+
+    pub(crate) fn record_delete(&mut self, key: &[u8]) {
+        self.deletes += 1;
+        self.deleted_bytes += key.len() as u64;
+    }
+}
+
+#[cfg(feature = "storage-stats")]
+pub(crate) use self::tracker::with_stats;
+
+#[cfg(feature = "storage-stats")]
+mod tracker {
+    use super::StorageStats;
+
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "std")] {
+            use std::cell::RefCell;
+            thread_local!(static STATS: RefCell<StorageStats> = RefCell::new(StorageStats::default()));
+
+            pub(crate) fn with_stats<R, F: FnOnce(&mut StorageStats) -> R>(f: F) -> R {
+                STATS.with(|stats| f(&mut stats.borrow_mut()))
+            }
+        } else {
+            static mut STATS: StorageStats = StorageStats {
+                reads: 0,
+                read_bytes: 0,
+                writes: 0,
+                written_bytes: 0,
+                deletes: 0,
+                deleted_bytes: 0,
+            };
+
+            pub(crate) fn with_stats<R, F: FnOnce(&mut StorageStats) -> R>(f: F) -> R {
+                //contracts are executed in a single thread, and no reference to STATS outlives the call
+                unsafe { f(&mut *core::ptr::addr_of_mut!(STATS)) }
+            }
+        }
+    }
+}
+
+///Storage usage recorded since the beginning of the invocation.
+///
+///# Example
+///```no_run
+/// # use ontio_std::database;
+///   database::put(b"key", 1u64);
+///   assert_eq!(database::stats().written_bytes, 3 + 8);
+///```
+#[cfg(feature = "storage-stats")]
+pub fn stats() -> StorageStats {
+    with_stats(|stats| *stats)
+}
+
+///Reset the recorded storage usage.
+#[cfg(feature = "storage-stats")]
+pub fn reset_stats() {
+    with_stats(|stats| *stats = StorageStats::default())
+}
@@ -0,0 +1,120 @@
+use crate::abi::{Decoder, Encoder, Sink, Source};
+use crate::database;
+use crate::prelude::*;
+use crate::runtime;
+use alloc::collections::BTreeMap;
+
+///Write cache on top of contract storage.
+///
+///Puts and deletes are buffered in memory and reads are served from the buffer first.
+///`commit` writes every buffered change to storage once, while `rollback` or dropping the
+///transaction discards them, so a multi-step operation can be abandoned without aborting the invocation.
+///
+///# Example
+///```no_run
+/// # use ontio_std::database::{self, Transaction};
+///   let mut tx = Transaction::new();
+///   tx.put("key", 1u64);
+///   assert_eq!(tx.get::<_, u64>("key"), Some(1));
+///   tx.rollback();
+///   assert_eq!(database::get::<_, u64>("key"), None);
+///```
+#[derive(Default)]
+pub struct Transaction {
+    overlay: BTreeMap<Vec<u8>, Option<Vec<u8>>>, //key, value. None means deleted
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Transaction { overlay: BTreeMap::new() }
+    }
+
+    #[track_caller]
+    pub fn get<K: AsRef<[u8]>, T>(&self, key: K) -> Option<T>
+    where
+        for<'a> T: Decoder<'a> + 'static,
+    {
+        match self.overlay.get(key.as_ref()) {
+            Some(Some(val)) => Some(Source::new(val).read().unwrap()),
+            Some(None) => None,
+            None => database::get(key),
+        }
+    }
+
+    pub fn contains_key<K: AsRef<[u8]>>(&self, key: K) -> bool {
+        match self.overlay.get(key.as_ref()) {
+            Some(val) => val.is_some(),
+            None => runtime::storage_read(key.as_ref()).is_some(),
+        }
+    }
+
+    pub fn put<K: AsRef<[u8]>, T: Encoder>(&mut self, key: K, val: T) {
+        let sink = Sink::with_value(&val);
+        self.overlay.insert(key.as_ref().to_vec(), Some(sink.into()));
+    }
+
+    pub fn delete<K: AsRef<[u8]>>(&mut self, key: K) {
+        self.overlay.insert(key.as_ref().to_vec(), None);
+    }
+
+    ///Number of buffered changes.
+    pub fn len(&self) -> usize {
+        self.overlay.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overlay.is_empty()
+    }
+
+    ///Write all buffered changes to storage.
+    pub fn commit(self) {
+        for (key, val) in self.overlay.iter() {
+            match val {
+                Some(val) => runtime::storage_write(key, val),
+                None => runtime::storage_delete(key),
+            }
+        }
+    }
+
+    ///Discard all buffered changes.
+    pub fn rollback(self) {}
+}
+
+#[test]
+fn test_commit() {
+    database::put("a", 1u64);
+    database::put("b", 2u64);
+    let mut tx = Transaction::new();
+    tx.put("a", 10u64);
+    tx.put("a", 11u64);
+    tx.delete("b");
+    tx.put("c", 3u64);
+    assert_eq!(tx.len(), 3);
+    assert_eq!(tx.get::<_, u64>("a"), Some(11));
+    assert_eq!(tx.get::<_, u64>("b"), None);
+    assert!(!tx.contains_key("b"));
+    assert_eq!(database::get::<_, u64>("a"), Some(1));
+    assert_eq!(database::get::<_, u64>("b"), Some(2));
+
+    tx.commit();
+    assert_eq!(database::get::<_, u64>("a"), Some(11));
+    assert_eq!(database::get::<_, u64>("b"), None);
+    assert_eq!(database::get::<_, u64>("c"), Some(3));
+}
+
+#[test]
+fn test_rollback() {
+    database::put("a", 1u64);
+    let mut tx = Transaction::new();
+    tx.put("a", 10u64);
+    tx.delete("a");
+    tx.put("b", 2u64);
+    tx.rollback();
+    assert_eq!(database::get::<_, u64>("a"), Some(1));
+    assert_eq!(database::get::<_, u64>("b"), None);
+
+    let mut tx = Transaction::new();
+    tx.put("b", 2u64);
+    drop(tx);
+    assert_eq!(database::get::<_, u64>("b"), None);
+}
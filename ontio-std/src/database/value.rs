@@ -0,0 +1,139 @@
+use crate::abi::{Decoder, Encoder};
+use crate::database;
+use crate::prelude::*;
+
+///Typed value stored under a fixed key.
+///
+///The value is read from storage on first access and cached, modifications are kept in memory
+///and written back by `flush`, which is called automatically when the `StorageValue` is dropped.
+///
+///# Example
+///```no_run
+/// # use ontio_std::database::StorageValue;
+/// # use ontio_std::types::U128;
+///   let mut total_supply: StorageValue<U128> = StorageValue::new(b"total_supply");
+///   *total_supply.or_default() += U128::new(100);
+///   assert_eq!(total_supply.get(), Some(&U128::new(100)));
+///```
+pub struct StorageValue<T: Encoder> {
+    key: Vec<u8>,
+    value: Option<Option<T>>, //None means not loaded yet
+    dirty: bool,
+}
+
+impl<T: Encoder> Drop for StorageValue<T> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl<T> StorageValue<T>
+where
+    for<'a> T: Decoder<'a> + Encoder + 'static,
+{
+    pub fn new(key: &[u8]) -> Self {
+        StorageValue { key: key.to_vec(), value: None, dirty: false }
+    }
+
+    fn load(&mut self) -> &mut Option<T> {
+        let key = &self.key;
+        self.value.get_or_insert_with(|| database::get(key))
+    }
+
+    pub fn get(&mut self) -> Option<&T> {
+        self.load().as_ref()
+    }
+
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if self.load().is_some() {
+            self.dirty = true;
+        }
+        self.load().as_mut()
+    }
+
+    pub fn exists(&mut self) -> bool {
+        self.load().is_some()
+    }
+
+    pub fn set(&mut self, val: T) {
+        self.dirty = true;
+        self.value = Some(Some(val));
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> T>(&mut self, default: F) -> &mut T {
+        self.dirty = true;
+        self.load().get_or_insert_with(default)
+    }
+
+    pub fn or_default(&mut self) -> &mut T
+    where
+        T: Default,
+    {
+        self.or_insert_with(T::default)
+    }
+
+    pub fn take(&mut self) -> Option<T> {
+        let val = self.load().take();
+        if val.is_some() {
+            self.dirty = true;
+        }
+        val
+    }
+}
+
+impl<T: Encoder> StorageValue<T> {
+    pub fn flush(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        if let Some(ref value) = self.value {
+            match value {
+                Some(val) => database::put(&self.key, val),
+                None => database::delete(&self.key),
+            }
+        }
+        self.dirty = false;
+    }
+}
+
+#[test]
+fn test_value() {
+    let mut admin: StorageValue<Address> = StorageValue::new(b"admin");
+    assert!(!admin.exists());
+    admin.set(Address::repeat_byte(1));
+    assert!(crate::runtime::storage_read(b"admin").is_none());
+    admin.flush();
+    let mut admin2: StorageValue<Address> = StorageValue::new(b"admin");
+    assert_eq!(admin2.get(), Some(&Address::repeat_byte(1)));
+
+    {
+        let mut supply: StorageValue<U128> = StorageValue::new(b"supply");
+        *supply.or_default() += U128::new(10);
+        *supply.or_default() += U128::new(5);
+    }
+    let mut supply: StorageValue<U128> = StorageValue::new(b"supply");
+    assert_eq!(supply.get(), Some(&U128::new(15)));
+    assert_eq!(supply.take(), Some(U128::new(15)));
+    drop(supply);
+    assert!(crate::runtime::storage_read(b"supply").is_none());
+}
+
+#[test]
+fn test_value_cached() {
+    let mut val: StorageValue<u64> = StorageValue::new(b"cached");
+    database::put(b"cached", 1u64);
+    assert_eq!(val.get(), Some(&1));
+    database::put(b"cached", 2u64);
+    assert_eq!(val.get(), Some(&1));
+}
+
+#[test]
+fn test_value_absent() {
+    let mut val: StorageValue<u64> = StorageValue::new(b"absent");
+    assert_eq!(val.get_mut(), None);
+    assert_eq!(val.take(), None);
+    //nothing to write back, the key is not deleted on flush
+    database::put(b"absent", 1u64);
+    drop(val);
+    assert_eq!(database::get(b"absent"), Some(1u64));
+}
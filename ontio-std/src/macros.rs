@@ -2,6 +2,7 @@ pub use ontio_codegen::base58;
 pub use ontio_codegen::contract;
 pub use ontio_codegen::event;
 pub use ontio_codegen::keccak256;
+pub use ontio_codegen::neo_contract;
 
 #[cfg(test)]
 mod tests {
@@ -42,6 +43,37 @@ mod tests {
         fn SliceParam(&self, from: &[Address]) {}
     }
 
+    #[test]
+    fn derive_error_path() {
+        use ontio_std::abi::{ErrorKind, Sink, Source};
+        #[derive(Encoder, Decoder)]
+        struct Transfer {
+            memo: String,
+            token: Oep4,
+        }
+        let mut sink = Sink::new(16);
+        sink.write("memo");
+        sink.write((1u32, 2u32));
+        sink.write_bytes(&[3]);
+        let err = Source::new(sink.bytes()).read::<Transfer>().err().unwrap();
+        let transfer =
+            Transfer { memo: "memo".to_string(), token: Oep4 { from: 1, to: 2, amt: 3 } };
+        assert_eq!(transfer.encoded_len(), Some(5 + 12));
+        assert_eq!(Token::Oep4(transfer.token).encoded_len(), Some(5 + 12));
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEOF);
+        assert_eq!(err.position(), 13);
+        assert_eq!(err.field_path(), "token.amt");
+        #[cfg(feature = "error-type-names")]
+        assert_eq!(err.type_name(), Some("u32"));
+        #[cfg(feature = "error-type-names")]
+        assert_eq!(
+            err.to_string(),
+            "unexpected end of data at byte 13 while decoding u32 in field token.amt"
+        );
+        #[cfg(not(feature = "error-type-names"))]
+        assert_eq!(err.to_string(), "unexpected end of data at byte 13 in field token.amt");
+    }
+
     #[test]
     fn base58() {
         const _ADDR: Address = ontio_std::macros::base58!("AFmseVrdL9f9oyCzZefL9tG6UbvhPbdYzM");
@@ -72,4 +104,272 @@ mod tests {
 
     #[test]
     fn event() {}
+
+    mod dispatch {
+        use crate as ontio_std;
+        use ontio_std::abi::{ContractError, DispatchError, Dispatcher, Sink, Source};
+        use ontio_std::prelude::*;
+
+        struct OverflowError;
+
+        impl ContractError for OverflowError {
+            fn code(&self) -> u32 {
+                1000
+            }
+
+            fn message(&self) -> String {
+                "overflow".to_string()
+            }
+        }
+
+        #[ontio_std::macros::contract]
+        trait Checked {
+            fn checked_add(&self, a: u32, b: u32) -> Result<u32, OverflowError>;
+            fn sum(&self, values: &[u32]) -> u32;
+            fn ignore(&mut self, value: u32);
+
+            #[event]
+            fn Overflow(&self, a: u32, b: &u32) {}
+        }
+
+        struct CheckedInstance;
+
+        impl Checked for CheckedInstance {
+            fn checked_add(&self, a: u32, b: u32) -> Result<u32, OverflowError> {
+                a.checked_add(b).ok_or(OverflowError)
+            }
+
+            fn sum(&self, values: &[u32]) -> u32 {
+                values.iter().sum()
+            }
+
+            fn ignore(&mut self, _value: u32) {}
+        }
+
+        fn invoke(payload: &[u8]) -> Result<Vec<u8>, DispatchError> {
+            CheckedDispatcher::new(CheckedInstance).try_dispatch(payload)
+        }
+
+        #[test]
+        fn dispatch_result() {
+            let mut sink = Sink::new(16);
+            sink.write(("checked_add", 1u32, 2u32));
+            let output = invoke(sink.bytes()).unwrap();
+            let res: Result<u32, DispatchError> = Source::new(&output).read().unwrap();
+            assert_eq!(res, Ok(3));
+
+            let mut sink = Sink::new(16);
+            sink.write(("checked_add", u32::MAX, 2u32));
+            let output = invoke(sink.bytes()).unwrap();
+            let res: Result<u32, DispatchError> = Source::new(&output).read().unwrap();
+            assert_eq!(res, Err(DispatchError::new(1000, "overflow")));
+        }
+
+        #[test]
+        fn dispatch_error() {
+            let mut sink = Sink::new(16);
+            sink.write(("checked_add", 1u32));
+            let err = invoke(sink.bytes()).unwrap_err();
+            assert_eq!(err.code, DispatchError::INVALID_ARGUMENT);
+            let message = "failed to decode argument: b, unexpected end of data at byte 16";
+            #[cfg(feature = "error-type-names")]
+            let message = format!("{} while decoding u32", message);
+            assert_eq!(err.message, message);
+
+            let mut sink = Sink::new(16);
+            sink.write("checked_sub");
+            let err = invoke(sink.bytes()).unwrap_err();
+            assert_eq!(err.code, DispatchError::UNSUPPORTED_METHOD);
+
+            let err = invoke(&[]).unwrap_err();
+            assert_eq!(err.code, DispatchError::INVALID_ARGUMENT);
+
+            let mut sink = Sink::new(16);
+            sink.write(("checked_add", 1u32, 2u32, 3u32));
+            let err = invoke(sink.bytes()).unwrap_err();
+            assert_eq!(
+                err.message,
+                "failed to decode argument: payload, trailing bytes at byte 20"
+            );
+
+            let mut sink = Sink::new(16);
+            sink.write("sum");
+            sink.write_bytes(&[0xfe, 0xff, 0xff, 0xff, 0x00]);
+            let err = invoke(sink.bytes()).unwrap_err();
+            let message = "failed to decode argument: values, inconsistent length at byte 9";
+            #[cfg(feature = "error-type-names")]
+            let message = format!("{} while decoding alloc::vec::Vec<u32>", message);
+            assert_eq!(err.message, message);
+        }
+
+        #[test]
+        #[should_panic(expected = "ontio panic")]
+        fn dispatch_abort() {
+            let mut sink = Sink::new(16);
+            sink.write("checked_sub");
+            CheckedDispatcher::new(CheckedInstance).dispatch(sink.bytes());
+        }
+
+        #[test]
+        fn client() {
+            let addr = Address::repeat_byte(1);
+            let expected = addr.clone();
+            ontio_std::mock::build_runtime().on_contract_call(move |addr, payload| {
+                assert_eq!(addr, &expected);
+                invoke(payload).unwrap()
+            });
+            let client = CheckedClient::new(addr);
+            assert_eq!(client.checked_add(1, 2), Ok(3));
+            assert_eq!(client.checked_add(u32::MAX, 2).unwrap_err().code, 1000);
+            assert_eq!(client.sum(&[1, 2, 3]), 6);
+            client.ignore(1);
+        }
+
+        #[test]
+        #[should_panic(expected = "failed to decode return value")]
+        fn client_error_output() {
+            ontio_std::mock::build_runtime().on_contract_call(|_, _| {
+                Sink::with_value(&Err::<u32, _>(DispatchError::new(1, "failed"))).into()
+            });
+            CheckedClient::new(Address::repeat_byte(1)).sum(&[1]);
+        }
+
+        #[test]
+        #[should_panic(expected = "failed to decode return value")]
+        fn client_unit_output() {
+            ontio_std::mock::build_runtime().on_contract_call(|_, _| vec![0]);
+            CheckedClient::new(Address::repeat_byte(1)).ignore(1);
+        }
+
+        #[test]
+        fn abi() {
+            assert_eq!(
+                CHECKED_ABI,
+                concat!(
+                    r#"{"name":"Checked","methods":["#,
+                    r#"{"name":"checked_add","parameters":[{"name":"a","type":"u32"},{"name":"b","type":"u32"}],"return_type":"Result<u32,DispatchError>"},"#,
+                    r#"{"name":"sum","parameters":[{"name":"values","type":"&[u32]"}],"return_type":"u32"},"#,
+                    r#"{"name":"ignore","parameters":[{"name":"value","type":"u32"}],"return_type":"()"}],"#,
+                    r#""events":[{"name":"Overflow","parameters":[{"name":"a","type":"u32"},{"name":"b","type":"&u32"}]}]}"#
+                )
+            );
+        }
+    }
+
+    mod borrowed {
+        use crate as ontio_std;
+        use ontio_std::abi::{Decoder, Dispatcher, Encoder, Sink, Source};
+        use ontio_std::prelude::*;
+
+        mod account {
+            use super::*;
+
+            #[derive(Encoder, Decoder)]
+            pub struct Address {
+                pub id: u32,
+            }
+        }
+
+        #[ontio_std::macros::contract]
+        trait Borrowed {
+            fn describe(
+                &self, name: &str, data: &[u8], owner: &ontio_std::types::Address, hash: &H256,
+            ) -> String;
+            fn account_id(&self, account: &account::Address) -> u32;
+        }
+
+        struct BorrowedInstance;
+
+        impl Borrowed for BorrowedInstance {
+            fn describe(&self, name: &str, data: &[u8], owner: &Address, hash: &H256) -> String {
+                format!(
+                    "{} {:?} {} {}",
+                    name,
+                    data,
+                    owner == &Address::repeat_byte(1),
+                    hash.is_zero()
+                )
+            }
+
+            fn account_id(&self, account: &account::Address) -> u32 {
+                account.id
+            }
+        }
+
+        #[test]
+        fn dispatch_borrowed() {
+            let mut sink = Sink::new(16);
+            sink.write(("describe", "name", &[1u8, 2][..], Address::repeat_byte(1), H256::zero()));
+            let output =
+                BorrowedDispatcher::new(BorrowedInstance).try_dispatch(sink.bytes()).unwrap();
+            let res: String = Source::new(&output).read().unwrap();
+            assert_eq!(res, "name [1, 2] true true");
+
+            let mut sink = Sink::new(16);
+            sink.write(("describe", "name", &[1u8, 2][..], Address::repeat_byte(1)));
+            sink.write_bytes(&[0; 31]);
+            let err =
+                BorrowedDispatcher::new(BorrowedInstance).try_dispatch(sink.bytes()).unwrap_err();
+            assert!(err.message.starts_with("failed to decode argument: hash,"));
+
+            let mut sink = Sink::new(16);
+            sink.write(("account_id", account::Address { id: 7 }));
+            let output =
+                BorrowedDispatcher::new(BorrowedInstance).try_dispatch(sink.bytes()).unwrap();
+            assert_eq!(Source::new(&output).read::<u32>().unwrap(), 7);
+        }
+    }
+
+    mod neo {
+        use crate as ontio_std;
+        use ontio_std::abi::event_builder::{TYPE_BOOL, TYPE_INT};
+        use ontio_std::abi::{Sink, VmValue, VmValueParser};
+        use ontio_std::prelude::*;
+
+        #[ontio_std::macros::neo_contract]
+        trait Oep4 {
+            fn total_supply(&self) -> U128;
+            fn balance_of(&self, owner: &Address) -> U128;
+            fn transfer(&self, from: &Address, to: &Address, amount: U128) -> bool;
+            #[name = "Init"]
+            fn init(&self);
+        }
+
+        #[test]
+        fn neo_client() {
+            let from = Address::repeat_byte(1);
+            let to = Address::repeat_byte(2);
+            ontio_std::mock::build_runtime().on_contract_call(move |addr, payload| {
+                assert_eq!(addr, &Address::repeat_byte(9));
+                let mut parser = VmValueParser::new(payload);
+                let (method, args): (String, VmValue) = parser.read().unwrap();
+                let mut sink = Sink::new(18);
+                sink.write_byte(0); //version
+                match (method.as_str(), args) {
+                    ("totalSupply", VmValue::List(args)) if args.is_empty() => {
+                        sink.write_byte(TYPE_INT);
+                        sink.write(U128::new(1000));
+                    }
+                    ("balanceOf", VmValue::List(args)) => {
+                        assert_eq!(args, vec![VmValue::Address(Address::repeat_byte(1))]);
+                        sink.write_byte(TYPE_INT);
+                        sink.write(U128::new(10));
+                    }
+                    ("transfer", VmValue::List(args)) => {
+                        assert_eq!(args[2], VmValue::Int(U128::new(5)));
+                        sink.write_byte(TYPE_BOOL);
+                        sink.write(args.len() == 3);
+                    }
+                    ("Init", _) => (),
+                    (method, _) => panic!("unexpected method {}", method),
+                }
+                sink.into()
+            });
+            let client = Oep4Client::new(Address::repeat_byte(9));
+            assert_eq!(client.total_supply(), U128::new(1000));
+            assert_eq!(client.balance_of(&from), U128::new(10));
+            assert!(client.transfer(&from, &to, U128::new(5)));
+            client.init();
+        }
+    }
 }
@@ -5,6 +5,7 @@ use self::runtime::setup_runtime;
 pub use self::runtime::Runtime;
 use self::runtime::RuntimeInner;
 use crate::abi::{Encoder, Sink};
+use crate::database::StorageStats;
 use crate::types::{Address, H256};
 use std::cell::RefCell;
 use std::iter::Iterator;
@@ -36,6 +37,11 @@ impl RuntimeHandle {
         self
     }
 
+    ///Storage usage of the contract since the runtime was built, writes done through the handle are not counted.
+    pub fn storage_stats(&self) -> StorageStats {
+        self.inner.borrow().storage_stats
+    }
+
     pub fn timestamp(&self, time: u64) -> &Self {
         self.inner.borrow_mut().timestamp = time;
         self
@@ -87,6 +93,8 @@ pub fn build_runtime() -> RuntimeHandle {
 
     let rt = Runtime { inner: inner.clone() };
     setup_runtime(rt);
+    #[cfg(feature = "storage-stats")]
+    crate::database::reset_stats();
 
     let handle = RuntimeHandle { inner: inner };
     handle
@@ -97,3 +105,25 @@ fn test_call_contract() {
     build_runtime().on_contract_call(|_addr, _data| -> Vec<u8> { vec![1, 2, 3] });
     assert_eq!(crate::runtime::call_contract(&Address::repeat_byte(1), &[1, 2]), vec![1, 2, 3]);
 }
+
+#[test]
+fn test_storage_stats() {
+    use crate::database;
+    let handle = build_runtime();
+    handle.storage_put(b"old", 1u32);
+    database::put(b"key", 1u64);
+    database::put(b"long", vec![1u8; 100]);
+    assert_eq!(database::get::<_, Vec<u8>>(b"long").unwrap().len(), 100);
+    assert_eq!(database::get::<_, u32>(b"none"), None);
+    database::delete(b"old");
+
+    let stats = handle.storage_stats();
+    assert_eq!(stats.writes, 2);
+    assert_eq!(stats.written_bytes, 3 + 8 + 4 + 101);
+    assert_eq!(stats.reads, 2);
+    assert_eq!(stats.read_bytes, 4 + 101 + 4);
+    assert_eq!(stats.deletes, 1);
+    assert_eq!(stats.deleted_bytes, 3);
+    #[cfg(feature = "storage-stats")]
+    assert_eq!(database::stats(), stats);
+}
@@ -1,6 +1,8 @@
+use crate::database::StorageStats;
 use crate::types::{Address, H256};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::ops::Bound;
 use std::rc::Rc;
 
 use sha2::Digest;
@@ -13,7 +15,7 @@ pub struct Runtime {
 
 #[derive(Default)]
 pub(crate) struct RuntimeInner {
-    pub(crate) storage: HashMap<Vec<u8>, Vec<u8>>,
+    pub(crate) storage: BTreeMap<Vec<u8>, Vec<u8>>,
     pub(crate) timestamp: u64,
     pub(crate) block_height: u64,
     pub(crate) caller: Address,
@@ -25,6 +27,7 @@ pub(crate) struct RuntimeInner {
     pub(crate) notify: Vec<Vec<u8>>,
     pub(crate) call_contract: Option<Box<dyn FnMut(&Address, &[u8]) -> Vec<u8>>>,
     pub(crate) call_output: Vec<u8>,
+    pub(crate) storage_stats: StorageStats,
 }
 
 impl RuntimeInner {
@@ -33,19 +36,43 @@ impl RuntimeInner {
         self.call_output = (call)(addr, data);
         self.call_output.len() as u32
     }
+
+    fn storage_next_key(&self, prefix: &[u8], after: &[u8]) -> Option<Vec<u8>> {
+        let start = if after < prefix { Bound::Included(prefix) } else { Bound::Excluded(after) };
+        let (key, _) = self.storage.range::<[u8], _>((start, Bound::Unbounded)).next()?;
+        if key.starts_with(prefix) {
+            Some(key.clone())
+        } else {
+            None
+        }
+    }
 }
 
 impl Runtime {
     fn storage_write(&self, key: &[u8], val: &[u8]) {
-        self.inner.borrow_mut().storage.insert(key.into(), val.to_vec());
+        let mut inner = self.inner.borrow_mut();
+        inner.storage_stats.record_write(key, val);
+        inner.storage.insert(key.into(), val.to_vec());
     }
 
-    fn storage_read(&self, key: &[u8]) -> Option<Vec<u8>> {
-        self.inner.borrow().storage.get(key).map(|val| val.to_vec())
+    //a value larger than the buffer is read in several calls, only the first one is recorded
+    fn storage_read(&self, key: &[u8], offset: usize) -> Option<Vec<u8>> {
+        let mut inner = self.inner.borrow_mut();
+        let val = inner.storage.get(key).map(|val| val.to_vec());
+        if offset == 0 {
+            inner.storage_stats.record_read(key, val.as_deref());
+        }
+        val
     }
 
     fn storage_delete(&self, key: &[u8]) {
-        self.inner.borrow_mut().storage.remove(key);
+        let mut inner = self.inner.borrow_mut();
+        inner.storage_stats.record_delete(key);
+        inner.storage.remove(key);
+    }
+
+    fn storage_next_key(&self, prefix: &[u8], after: &[u8]) -> Option<Vec<u8>> {
+        self.inner.borrow().storage_next_key(prefix, after)
     }
 
     fn timestamp(&self) -> u64 {
@@ -177,7 +204,7 @@ mod env {
     ) -> u32 {
         let offset = offset as usize;
         let key = slice::from_raw_parts(key, klen as usize);
-        let v = RUNTIME.with(|r| r.borrow().storage_read(key));
+        let v = RUNTIME.with(|r| r.borrow().storage_read(key, offset));
         match v {
             None => u32::MAX,
             Some(v) => {
@@ -206,6 +233,22 @@ mod env {
         RUNTIME.with(|r| r.borrow().storage_delete(key));
     }
 
+    #[no_mangle]
+    pub unsafe extern "C" fn ontio_storage_next_key(
+        prefix: *const u8, plen: u32, after: *const u8, alen: u32, key: *mut u8, klen: u32,
+    ) -> u32 {
+        let prefix = slice::from_raw_parts(prefix, plen as usize);
+        let after = slice::from_raw_parts(after, alen as usize);
+        let k = RUNTIME.with(|r| r.borrow().storage_next_key(prefix, after));
+        match k {
+            None => u32::MAX,
+            Some(k) => {
+                ptr::copy(k.as_ptr(), key, cmp::min(klen as usize, k.len()));
+                k.len() as u32
+            }
+        }
+    }
+
     #[no_mangle]
     pub unsafe extern "C" fn ontio_notify(ptr: *const u8, len: u32) {
         let msg = slice::from_raw_parts(ptr, len as usize);
@@ -31,6 +31,10 @@ mod env {
         ) -> u32;
         pub fn ontio_storage_write(key: *const u8, klen: u32, val: *const u8, vlen: u32);
         pub fn ontio_storage_delete(key: *const u8, klen: u32);
+        #[cfg(feature = "storage-iter")]
+        pub fn ontio_storage_next_key(
+            prefix: *const u8, plen: u32, after: *const u8, alen: u32, key: *mut u8, klen: u32,
+        ) -> u32;
         pub fn ontio_sha256(data: *const u8, len: u32, val: *mut u8);
         pub fn ontio_contract_create(
             code_ptr: *const u8, code_len: u32, vm_type: u32, name_ptr: *const u8, name_len: u32,
@@ -199,6 +203,8 @@ pub fn contract_delete() -> ! {
 /// ```
 ///
 pub fn storage_write(key: &[u8], val: &[u8]) {
+    #[cfg(feature = "storage-stats")]
+    crate::database::with_stats(|stats| stats.record_write(key, val));
     unsafe {
         env::ontio_storage_write(key.as_ptr(), key.len() as u32, val.as_ptr(), val.len() as u32);
     }
@@ -215,6 +221,8 @@ pub fn storage_write(key: &[u8], val: &[u8]) {
 /// ```
 ///
 pub fn storage_delete(key: &[u8]) {
+    #[cfg(feature = "storage-stats")]
+    crate::database::with_stats(|stats| stats.record_delete(key));
     unsafe {
         env::ontio_storage_delete(key.as_ptr(), key.len() as u32);
     }
@@ -243,6 +251,8 @@ pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
     };
 
     if size == core::u32::MAX {
+        #[cfg(feature = "storage-stats")]
+        crate::database::with_stats(|stats| stats.record_read(key, None));
         return None;
     }
     let size = size as usize;
@@ -261,9 +271,63 @@ pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
         };
     }
 
+    #[cfg(feature = "storage-stats")]
+    crate::database::with_stats(|stats| stats.record_read(key, Some(&val)));
     Some(val)
 }
 
+///Find the smallest key which starts with `prefix` and is greater than `after`, used to scan storage in key order.
+///
+/// Only available with the `storage-iter` feature, which imports the `ontio_storage_next_key` host function:
+/// a contract built with it can not be deployed on a node which does not provide it.
+///
+/// # Example
+///
+/// ```no_run
+/// # use ontio_std::runtime;
+/// let mut after = Vec::new();
+/// while let Some(key) = runtime::storage_next_key(b"balance", &after) {
+///     let value = runtime::storage_read(&key);
+///     after = key;
+/// }
+/// ```
+///
+#[cfg(feature = "storage-iter")]
+pub fn storage_next_key(prefix: &[u8], after: &[u8]) -> Option<Vec<u8>> {
+    const INITIAL: usize = 64;
+    let mut key = vec![0; INITIAL];
+    let size = unsafe {
+        env::ontio_storage_next_key(
+            prefix.as_ptr(),
+            prefix.len() as u32,
+            after.as_ptr(),
+            after.len() as u32,
+            key.as_mut_ptr(),
+            key.len() as u32,
+        )
+    };
+
+    if size == core::u32::MAX {
+        return None;
+    }
+    let size = size as usize;
+    key.resize(size, 0);
+    if size > INITIAL {
+        unsafe {
+            env::ontio_storage_next_key(
+                prefix.as_ptr(),
+                prefix.len() as u32,
+                after.as_ptr(),
+                after.len() as u32,
+                key.as_mut_ptr(),
+                key.len() as u32,
+            )
+        };
+    }
+
+    Some(key)
+}
+
 /// Get timestamp in current block
 /// # Example
 ///